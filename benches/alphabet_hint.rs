@@ -0,0 +1,46 @@
+//! Compare inserting DNA k-mers into a Trie with and without an alphabet hint
+//!
+//! Run with `cargo bench --bench alphabet_hint`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::xorshift;
+use trie::Trie;
+
+fn random_kmers(count: usize, k: usize) -> Vec<Vec<u8>> {
+    let mut next = xorshift(0x9e37_79b9_7f4a_7c15);
+    (0..count)
+        .map(|_| (0..k).map(|_| b"ACGT"[(next() % 4) as usize]).collect())
+        .collect()
+}
+
+fn main() {
+    let kmers = random_kmers(200_000, 16);
+    let rounds = 5;
+
+    let start = Instant::now();
+    let mut total = 0;
+    for _ in 0..rounds {
+        let mut t = Trie::new_empty();
+        for (i, key) in kmers.iter().enumerate() {
+            t.insert(key.iter().cloned(), i).ok();
+        }
+        total += t.len();
+    }
+    println!("no hint:  {:>10?} ({} entries)", start.elapsed(), total);
+
+    let start = Instant::now();
+    let mut total = 0;
+    for _ in 0..rounds {
+        let mut builder = Trie::with_alphabet_hint(4);
+        for (i, key) in kmers.iter().enumerate() {
+            builder.insert(key.iter().cloned(), i).ok();
+        }
+        total += builder.build().len();
+    }
+    println!("hint = 4: {:>10?} ({} entries)", start.elapsed(), total);
+}
@@ -0,0 +1,65 @@
+//! Compare append against draining one Trie and inserting its entries into another
+//!
+//! Run with `cargo bench --bench append`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::random_words;
+use trie::Trie;
+
+fn build<'a, I: Iterator<Item=&'a String>>(words: I) -> Trie<char, usize> {
+    let mut t = Trie::new_empty();
+    for (i, word) in words.enumerate() {
+        t.insert(word.chars(), i).ok();
+    }
+    t
+}
+
+/// Time both ways of combining each pair of disjoint Tries
+fn compare(name: &str, left: &Trie<char, usize>, right: &Trie<char, usize>) {
+    let rounds = 10;
+
+    let pairs: Vec<_> = (0..rounds).map(|_| (left.clone(), right.clone())).collect();
+    let start = Instant::now();
+    let mut merged = Vec::with_capacity(rounds);
+    for (mut t, mut other) in pairs {
+        for (key, i) in other.drain() {
+            t.insert(key, i).ok();
+        }
+        merged.push(t);
+    }
+    let elapsed = start.elapsed();
+    // dropping the results isn't part of either approach, so it's left out of the timings
+    let total: usize = merged.iter().map(Trie::len).sum();
+    println!("{} drain+insert: {:>10?} ({} entries)", name, elapsed, total);
+
+    let pairs: Vec<_> = (0..rounds).map(|_| (left.clone(), right.clone())).collect();
+    let start = Instant::now();
+    let mut merged = Vec::with_capacity(rounds);
+    for (mut t, other) in pairs {
+        t.append(other);
+        merged.push(t);
+    }
+    let elapsed = start.elapsed();
+    let total: usize = merged.iter().map(Trie::len).sum();
+    println!("{} append:       {:>10?} ({} entries)", name, elapsed, total);
+}
+
+fn main() {
+    let mut words = random_words(100_000, 3);
+    words.sort();
+    words.dedup();
+
+    // split by first letter, so the two Tries share no root children
+    let (low, high): (Vec<&String>, Vec<&String>) = words.iter().partition(|word| word.as_str() < "n");
+    compare("by letter:  ", &build(low.into_iter()), &build(high.into_iter()));
+
+    // alternate words, so the two Tries share most short prefixes
+    let evens = build(words.iter().step_by(2));
+    let odds = build(words.iter().skip(1).step_by(2));
+    compare("interleaved:", &evens, &odds);
+}
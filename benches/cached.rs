@@ -0,0 +1,53 @@
+//! Compare repeated lookups on a Trie against a CachedTrie, with hot keys queried far more
+//! often than cold ones
+//!
+//! Run with `cargo bench --bench cached`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::{random_words, xorshift};
+use trie::{CachedTrie, Trie};
+
+/// Indices into `0..n` following a Zipf distribution (exponent 1): index `i` is drawn with
+/// probability proportional to `1 / (i + 1)`
+fn zipf_indices(n: usize, count: usize) -> Vec<usize> {
+    let mut cumulative = Vec::with_capacity(n);
+    let mut total = 0.0;
+    for i in 0..n {
+        total += 1.0 / (i + 1) as f64;
+        cumulative.push(total);
+    }
+    let mut next = xorshift(0x2545_f491_4f6c_dd1d);
+    (0..count)
+        .map(|_| {
+            let target = (next() >> 11) as f64 / (1u64 << 53) as f64 * total;
+            match cumulative.binary_search_by(|c| c.partial_cmp(&target).unwrap()) {
+                Ok(i) | Err(i) => i.min(n - 1),
+            }
+        })
+        .collect()
+}
+
+fn main() {
+    let words = random_words(100_000, 8);
+    let mut t = Trie::new_empty();
+    for (i, word) in words.iter().enumerate() {
+        t.insert(word.chars(), i).ok();
+    }
+    let queries: Vec<&String> = zipf_indices(words.len(), 1_000_000).into_iter().map(|i| &words[i]).collect();
+
+    let start = Instant::now();
+    let found = queries.iter().filter(|word| t.get(word.chars()).is_some()).count();
+    println!("Trie:                     get {:>10?} ({} found)", start.elapsed(), found);
+
+    for &capacity in [256, 4096].iter() {
+        let mut cached = CachedTrie::from_trie(t.clone(), capacity);
+        let start = Instant::now();
+        let found = queries.iter().filter(|word| cached.get(word.chars()).is_some()).count();
+        println!("CachedTrie (capacity {:>4}): get {:>10?} ({} found)", capacity, start.elapsed(), found);
+    }
+}
@@ -0,0 +1,25 @@
+//! Input generation shared by the benchmarks
+//!
+//! Each benchmark only uses some of these.
+#![allow(dead_code)]
+
+/// xorshift, so the benchmarks don't need a rand dependency
+pub fn xorshift(mut state: u64) -> impl FnMut() -> u64 {
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}
+
+/// `count` random lowercase words, each `min_len` to `min_len + 9` letters long
+pub fn random_words(count: usize, min_len: usize) -> Vec<String> {
+    let mut next = xorshift(0x9e37_79b9_7f4a_7c15);
+    (0..count)
+        .map(|_| {
+            let len = min_len + (next() % 10) as usize;
+            (0..len).map(|_| (b'a' + (next() % 26) as u8) as char).collect()
+        })
+        .collect()
+}
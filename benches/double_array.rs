@@ -0,0 +1,52 @@
+//! Compare lookup throughput of a byte Trie against its frozen and double-array forms
+//!
+//! Run with `cargo bench --bench double_array`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::random_words;
+use trie::Trie;
+
+fn main() {
+    let words: Vec<Vec<u8>> = random_words(100_000, 3)
+        .into_iter()
+        .map(String::into_bytes)
+        .collect();
+    let mut t = Trie::new_empty();
+    for (i, word) in words.iter().enumerate() {
+        t.insert(word.iter().cloned(), i).ok();
+    }
+
+    let start = Instant::now();
+    let da = t.build_double_array();
+    println!("DoubleArrayTrie: build {:>10?}", start.elapsed());
+    let start = Instant::now();
+    let frozen = t.clone().freeze();
+    println!("FrozenTrie:      build {:>10?}", start.elapsed());
+    let rounds = 10;
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| t.get(word.iter().cloned()).is_some()).count();
+    }
+    println!("Trie:            get   {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| frozen.get(word.iter().cloned()).is_some()).count();
+    }
+    println!("FrozenTrie:      get   {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| da.get(word).is_some()).count();
+    }
+    println!("DoubleArrayTrie: get   {:>10?} ({} found)", start.elapsed(), found);
+}
@@ -0,0 +1,41 @@
+//! Compare building a Trie from sorted keys with from_sorted against inserting them one by one
+//!
+//! Run with `cargo bench --bench from_sorted`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::random_words;
+use trie::Trie;
+
+fn main() {
+    let mut words = random_words(100_000, 3);
+    words.sort();
+    words.dedup();
+    let pairs: Vec<(Vec<char>, usize)> = words.iter()
+        .enumerate()
+        .map(|(i, word)| (word.chars().collect(), i))
+        .collect();
+    let rounds = 10;
+
+    let start = Instant::now();
+    let mut total = 0;
+    for _ in 0..rounds {
+        let mut t = Trie::new_empty();
+        for (key, i) in pairs.iter().cloned() {
+            t.insert(key, i).ok();
+        }
+        total += t.len();
+    }
+    println!("insert:      {:>10?} ({} entries)", start.elapsed(), total);
+
+    let start = Instant::now();
+    let mut total = 0;
+    for _ in 0..rounds {
+        total += Trie::from_sorted(pairs.iter().cloned()).len();
+    }
+    println!("from_sorted: {:>10?} ({} entries)", start.elapsed(), total);
+}
@@ -0,0 +1,50 @@
+//! Compare lookup throughput of a Trie against its frozen form
+//!
+//! Run with `cargo bench --bench frozen`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::random_words;
+use trie::Trie;
+
+fn main() {
+    let words = random_words(100_000, 3);
+    let mut t = Trie::new_empty();
+    for (i, word) in words.iter().enumerate() {
+        t.insert(word.chars(), i).ok();
+    }
+    let frozen = t.clone().freeze();
+    let rounds = 10;
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| t.get(word.chars()).is_some()).count();
+    }
+    println!("Trie:       get    {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| frozen.get(word.chars()).is_some()).count();
+    }
+    println!("FrozenTrie: get    {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| t.search(word.chars()).is_some()).count();
+    }
+    println!("Trie:       search {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| frozen.search(word.chars()).is_some()).count();
+    }
+    println!("FrozenTrie: search {:>10?} ({} found)", start.elapsed(), found);
+}
@@ -0,0 +1,48 @@
+//! Compare the Vec-based Trie against HashTrie for wide fan-out byte keys
+//!
+//! Run with `cargo bench --bench hash_trie`
+
+extern crate trie;
+
+mod common;
+
+use std::time::Instant;
+
+use common::xorshift;
+use trie::{HashTrie, Trie};
+
+fn random_keys(count: usize) -> Vec<Vec<u8>> {
+    let mut next = xorshift(0x2545_f491_4f6c_dd1d);
+    (0..count)
+        .map(|_| {
+            let len = 4 + (next() % 12) as usize;
+            (0..len).map(|_| next() as u8).collect()
+        })
+        .collect()
+}
+
+fn main() {
+    let keys = random_keys(100_000);
+
+    let start = Instant::now();
+    let mut t = Trie::new_empty();
+    for (i, key) in keys.iter().enumerate() {
+        t.insert(key.iter().cloned(), i).ok();
+    }
+    println!("Trie:     insert {:>10?}", start.elapsed());
+
+    let start = Instant::now();
+    let found = keys.iter().filter(|key| t.get(key.iter().cloned()).is_some()).count();
+    println!("Trie:     get    {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut h = HashTrie::new_empty();
+    for (i, key) in keys.iter().enumerate() {
+        h.insert(key.iter().cloned(), i).ok();
+    }
+    println!("HashTrie: insert {:>10?}", start.elapsed());
+
+    let start = Instant::now();
+    let found = keys.iter().filter(|key| h.get(key.iter().cloned()).is_some()).count();
+    println!("HashTrie: get    {:>10?} ({} found)", start.elapsed(), found);
+}
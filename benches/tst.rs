@@ -0,0 +1,70 @@
+//! Compare the memory use and lookup throughput of a Trie against a TstTrie
+//!
+//! Run with `cargo bench --bench tst`
+
+extern crate trie;
+
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use common::random_words;
+use trie::{Trie, TstTrie};
+
+/// Counts the bytes currently allocated, so the benchmark can report each structure's size
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let words = random_words(100_000, 3);
+    let rounds = 10;
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut t = Trie::new_empty();
+    for (i, word) in words.iter().enumerate() {
+        t.insert(word.chars(), i).ok();
+    }
+    let trie_bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut tst = TstTrie::new_empty();
+    for (i, word) in words.iter().enumerate() {
+        tst.insert(word.chars(), i).ok();
+    }
+    let tst_bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    println!("Trie:    {:>10} bytes", trie_bytes);
+    println!("TstTrie: {:>10} bytes", tst_bytes);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| t.get(word.chars()).is_some()).count();
+    }
+    println!("Trie:    get    {:>10?} ({} found)", start.elapsed(), found);
+
+    let start = Instant::now();
+    let mut found = 0;
+    for _ in 0..rounds {
+        found += words.iter().filter(|word| tst.get(word.chars()).is_some()).count();
+    }
+    println!("TstTrie: get    {:>10?} ({} found)", start.elapsed(), found);
+}
@@ -0,0 +1,80 @@
+use {ErrType, Trie, TrieError};
+
+/// Builds a [Trie] whose nodes reserve room for a fixed number of children, made with
+/// [Trie::with_alphabet_hint]
+///
+/// The hint lives here for the length of a bulk insert rather than in every node, and is passed
+/// down as each key is inserted.
+pub struct TrieBuilder<K, D> {
+    trie: Trie<K, D>,
+    hint: usize,
+}
+
+impl<K: PartialEq + Copy, D> TrieBuilder<K, D> {
+
+    pub(crate) fn new(hint: usize) -> TrieBuilder<K, D> {
+        TrieBuilder {
+            trie: Trie::with_capacity(hint),
+            hint,
+        }
+    }
+
+    /// Insert a new value through an iterator, as [Trie::insert_iter] does
+    ///
+    /// A node's first child reserves room for the hinted number of children, so leaves
+    /// allocate nothing.
+    pub fn insert_iter<F: Iterator<Item=K>>(&mut self, key_elems: F, data: D) -> ErrType {
+        let mut node = &mut self.trie;
+        for key_elem in key_elems {
+            // no capacity yet means no children yet, so this one is the first
+            if node.children.capacity() == 0 {
+                node.children.reserve_exact(self.hint);
+            }
+            node = node.child_or_insert(key_elem);
+        }
+        if node.data.is_some() {
+            return Err(TrieError::KeyAlreadyPresent);
+        }
+        node.data = Some(data);
+        Ok(())
+    }
+
+    /// Insert a new value, as [Trie::insert] does
+    ///
+    /// Syntactic sugar for [self.insert_iter]
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        self.insert_iter(key.into_iter(), data)
+    }
+
+    /// Finish building, returning the Trie
+    pub fn build(self) -> Trie<K, D> {
+        self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Trie, TrieError};
+
+    #[test]
+    fn test_alphabet_hint() {
+        let mut hinted = Trie::with_alphabet_hint(4);
+        let mut plain = Trie::new_empty();
+        for (i, kmer) in ["ACGT", "ACGA", "TTAG", "AC", "GGGG"].iter().enumerate() {
+            assert_eq!(hinted.insert(kmer.chars(), i), Ok(()));
+            assert_eq!(plain.insert(kmer.chars(), i), Ok(()));
+        }
+        assert_eq!(hinted.insert("AC".chars(), 9), Err(TrieError::KeyAlreadyPresent));
+
+        let hinted = hinted.build();
+        assert_eq!(hinted, plain);
+        assert!(hinted.children.capacity() >= 4);
+        let node = hinted.find_node("TTA".chars()).unwrap();
+        assert!(node.children.capacity() >= 4);
+        let node = hinted.find_node("AC".chars()).unwrap();
+        assert!(node.children.capacity() >= 4);
+
+        // leaves don't reserve anything
+        assert_eq!(hinted.find_node("ACGT".chars()).unwrap().children.capacity(), 0);
+    }
+}
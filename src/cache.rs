@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash};
+
+use fnv::FnvHasher;
+use {ErrType, Trie};
+
+/// How many lookups a [CachedTrie] remembers unless told otherwise
+const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// A Trie which remembers the results of recent lookups
+///
+/// `search` and `get` check a bounded least-recently-used cache, keyed by the whole query,
+/// before walking the Trie, which pays off when the same hot keys are queried over and over.
+/// Results are cloned into the cache, and any change to the Trie throws the whole cache away.
+/// Lookups take `&mut self`, since they update the cache.
+///
+/// A hit still costs collecting the query into a `Vec` and hashing it, which is about what a
+/// plain walk down a Trie of short keys costs when its nodes are already in the CPU cache, so
+/// measure (see `cargo bench --bench cached`) before reaching for this.
+#[derive(Clone)]
+pub struct CachedTrie<K: Hash + Eq, D> {
+    trie: Trie<K, D>,
+    cache: LruCache<(Lookup, Vec<K>), Option<D>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Lookup {
+    Search,
+    Get,
+}
+
+impl<K: Eq + Copy + Hash, D: Clone> CachedTrie<K, D> {
+
+    /// Construct a new, empty CachedTrie with the default cache size
+    pub fn new_empty() -> CachedTrie<K, D> {
+        CachedTrie::with_cache_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Construct a new, empty CachedTrie remembering up to `capacity` lookups
+    ///
+    /// A capacity of 0 turns caching off.
+    pub fn with_cache_capacity(capacity: usize) -> CachedTrie<K, D> {
+        CachedTrie::from_trie(Trie::new_empty(), capacity)
+    }
+
+    /// Wrap an existing Trie, remembering up to `capacity` lookups
+    pub fn from_trie(trie: Trie<K, D>, capacity: usize) -> CachedTrie<K, D> {
+        CachedTrie {
+            trie,
+            cache: LruCache::new(capacity),
+        }
+    }
+
+    /// Search for the longest match in the Trie, through the cache
+    pub fn search<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&D> {
+        self.lookup(Lookup::Search, key.into_iter().collect())
+    }
+
+    /// Look up the value stored at exactly this key, through the cache
+    pub fn get<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&D> {
+        self.lookup(Lookup::Get, key.into_iter().collect())
+    }
+
+    /// Insert a new value into the Trie, clearing the cache
+    ///
+    /// Inserting a key that is already present is illegal, and leaves the cache alone.
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        self.trie.insert(key, data)?;
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Insert a value, replacing and returning any value already stored at the key, and clear
+    /// the cache
+    pub fn insert_or_replace<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> Option<D> {
+        self.cache.clear();
+        self.trie.insert_or_replace(key, data)
+    }
+
+    /// Remove a key from the Trie, returning its value, and clear the cache if it was there
+    pub fn remove<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<D> {
+        let removed = self.trie.remove(key);
+        if removed.is_some() {
+            self.cache.clear();
+        }
+        removed
+    }
+
+    /// The underlying Trie, for lookups which bypass the cache
+    pub fn as_trie(&self) -> &Trie<K, D> {
+        &self.trie
+    }
+
+    /// Consume the CachedTrie, returning the underlying Trie
+    pub fn into_trie(self) -> Trie<K, D> {
+        self.trie
+    }
+
+    fn lookup(&mut self, lookup: Lookup, key: Vec<K>) -> Option<&D> {
+        let trie = &self.trie;
+        let find = |key: &[K]| match lookup {
+            Lookup::Search => trie.search(key.iter().cloned()),
+            Lookup::Get => trie.get(key.iter().cloned()),
+        };
+        if self.cache.capacity == 0 {
+            return find(&key);
+        }
+        self.cache.get_or_insert_with((lookup, key), |query| find(&query.1).cloned()).as_ref()
+    }
+}
+
+impl<K: Eq + Copy + Hash, D: Clone> Default for CachedTrie<K, D> {
+
+    fn default() -> CachedTrie<K, D> {
+        CachedTrie::new_empty()
+    }
+}
+
+/// Marks the end of the recency list in an [LruCache]
+const NIL: usize = usize::MAX;
+
+/// A fixed-capacity map which forgets its least recently used entry to make room
+///
+/// Entries live in a `Vec`, threaded into a doubly linked list from most to least recently
+/// used by index, so lookups, insertions and evictions are all O(1).
+#[derive(Clone, Debug)]
+struct LruCache<Q: Hash + Eq, V> {
+    index: HashMap<Q, usize, BuildHasherDefault<FnvHasher>>,
+    entries: Vec<LruEntry<Q, V>>,
+    capacity: usize,
+    /// most recently used
+    head: usize,
+    /// least recently used, and the next to go
+    tail: usize,
+}
+
+#[derive(Clone, Debug)]
+struct LruEntry<Q, V> {
+    key: Q,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+impl<Q: Hash + Eq + Clone, V> LruCache<Q, V> {
+
+    fn new(capacity: usize) -> LruCache<Q, V> {
+        LruCache {
+            index: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    /// Look up an entry, first storing `f(&key)` under the key if it's not there, and mark it
+    /// most recently used
+    ///
+    /// Storing a new entry in a full cache evicts the least recently used one. The capacity
+    /// must not be 0.
+    fn get_or_insert_with<G: FnOnce(&Q) -> V>(&mut self, key: Q, f: G) -> &V {
+        let i = match self.index.get(&key) {
+            Some(&i) => {
+                self.unlink(i);
+                i
+            },
+            None => {
+                let value = f(&key);
+                self.store(key, value)
+            },
+        };
+        self.push_front(i);
+        &self.entries[i].value
+    }
+
+    /// Find room for a new entry, evicting if full, and return its (unlinked) index
+    fn store(&mut self, key: Q, value: V) -> usize {
+        debug_assert!(self.capacity > 0);
+        let i = if self.entries.len() < self.capacity {
+            self.entries.push(LruEntry { key: key.clone(), value, prev: NIL, next: NIL });
+            self.entries.len() - 1
+        } else {
+            let i = self.tail;
+            self.unlink(i);
+            self.index.remove(&self.entries[i].key);
+            self.entries[i].key = key.clone();
+            self.entries[i].value = value;
+            i
+        };
+        self.index.insert(key, i);
+        i
+    }
+
+    fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (prev, next) = (self.entries[i].prev, self.entries[i].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        self.entries[i].prev = NIL;
+        self.entries[i].next = self.head;
+        match self.head {
+            NIL => self.tail = i,
+            head => self.entries[head].prev = i,
+        }
+        self.head = i;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+    use super::{CachedTrie, LruCache};
+
+    fn words() -> Trie<char, usize> {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["a", "ab", "abc", "b", "banana"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_cached_matches_trie() {
+        let t = words();
+        let mut cached = CachedTrie::from_trie(t.clone(), 4);
+        let queries = ["a", "abcd", "ab", "x", "ban", "banana", "a", "abcd", "x", "ba", "abc", "a"];
+        for _ in 0..3 {
+            for query in queries.iter() {
+                assert_eq!(cached.get(query.chars()), t.get(query.chars()), "get {:?}", query);
+                assert_eq!(cached.search(query.chars()), t.search(query.chars()), "search {:?}", query);
+            }
+        }
+        assert_eq!(cached.cache.entries.len(), 4);
+
+        let mut uncached = CachedTrie::from_trie(t.clone(), 0);
+        for query in queries.iter() {
+            assert_eq!(uncached.search(query.chars()), t.search(query.chars()));
+        }
+        assert_eq!(uncached.cache.entries.len(), 0);
+    }
+
+    #[test]
+    fn test_mutation_invalidates() {
+        let mut cached = CachedTrie::from_trie(words(), 16);
+        assert_eq!(cached.get("abcd".chars()), None);
+        assert_eq!(cached.search("abcd".chars()), Some(&2));
+
+        assert_eq!(cached.insert("abcd".chars(), 10), Ok(()));
+        assert_eq!(cached.get("abcd".chars()), Some(&10));
+        assert_eq!(cached.search("abcd".chars()), Some(&10));
+
+        assert_eq!(cached.insert_or_replace("abcd".chars(), 11), Some(10));
+        assert_eq!(cached.get("abcd".chars()), Some(&11));
+
+        assert_eq!(cached.remove("abcd".chars()), Some(11));
+        assert_eq!(cached.get("abcd".chars()), None);
+        assert_eq!(cached.search("abcd".chars()), Some(&2));
+
+        // a failed insert changes nothing, so the cache survives
+        assert!(cached.insert("a".chars(), 0).is_err());
+        assert_eq!(cached.cache.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cached = |lru: &LruCache<&str, usize>, key| lru.index.contains_key(&key);
+        let mut lru = LruCache::new(2);
+        assert_eq!(lru.get_or_insert_with("a", |_| 1), &1);
+        assert_eq!(lru.get_or_insert_with("b", |_| 2), &2);
+        // touching "a" makes "b" the least recently used
+        assert_eq!(lru.get_or_insert_with("a", |_| panic!("already cached")), &1);
+        assert_eq!(lru.get_or_insert_with("c", |_| 3), &3);
+        assert!(!cached(&lru, "b"));
+        assert!(cached(&lru, "a"));
+        assert!(cached(&lru, "c"));
+        assert_eq!(lru.get_or_insert_with("d", |_| 4), &4);
+        assert!(!cached(&lru, "a"));
+        assert_eq!(lru.entries.len(), 2);
+
+        lru.clear();
+        assert!(!cached(&lru, "d"));
+        assert_eq!(lru.get_or_insert_with("e", |_| 5), &5);
+        assert_eq!(lru.entries.len(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+use core::fmt;
+
+use {ErrType, Trie, TrieError};
+
+/// Decides whether two key elements match, for a [TrieWith]
+pub trait ElementCmp<K> {
+
+    /// Check whether a query element matches a stored one
+    fn eq(&self, a: &K, b: &K) -> bool;
+}
+
+/// Matches key elements with `==`, just like a plain [Trie]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Exact;
+
+impl<K: PartialEq> ElementCmp<K> for Exact {
+
+    fn eq(&self, a: &K, b: &K) -> bool {
+        a == b
+    }
+}
+
+/// Matches characters regardless of case, using Unicode's lowercase mapping
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CaseInsensitive;
+
+impl ElementCmp<char> for CaseInsensitive {
+
+    fn eq(&self, a: &char, b: &char) -> bool {
+        a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+/// A Trie which matches key elements with a custom comparison instead of `==`
+///
+/// Descending a level means scanning the children for the first (in the order they were added)
+/// that `C` says matches. Keys are stored as they were first inserted: with [CaseInsensitive],
+/// inserting "Abc" and then looking up "aBC" finds it, and inserting "ABC" fails as a duplicate.
+#[derive(Clone)]
+pub struct TrieWith<K, D, C> {
+    trie: Trie<K, D>,
+    cmp: C,
+}
+
+impl<K: PartialEq + Copy, D, C: ElementCmp<K>> TrieWith<K, D, C> {
+
+    /// Construct a new, empty TrieWith matching key elements with `cmp`
+    pub fn with_cmp(cmp: C) -> TrieWith<K, D, C> {
+        TrieWith::from_trie(Trie::new_empty(), cmp)
+    }
+
+    /// Match the keys of an existing Trie with `cmp`
+    pub fn from_trie(trie: Trie<K, D>, cmp: C) -> TrieWith<K, D, C> {
+        TrieWith { trie, cmp }
+    }
+
+    /// Insert a new value into the TrieWith
+    ///
+    /// Inserting a key which matches one already present is illegal.
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        let cmp = &self.cmp;
+        let mut node = &mut self.trie;
+        for key_elem in key {
+            let found = node.children.iter().position(|child| child_matches(cmp, child, &key_elem));
+            let index = match found {
+                Some(index) => index,
+                None => {
+                    node.children.push(Trie::new_node(key_elem));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+        }
+        match node.data {
+            None => {
+                node.data = Some(data);
+                Ok(())
+            }
+            Some(_) => Err(TrieError::KeyAlreadyPresent),
+        }
+    }
+
+    /// Search for the longest match in the TrieWith
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &self.trie;
+        let mut longest = node.data.as_ref();
+        for key_elem in key {
+            node = match self.child(node, &key_elem) {
+                Some(child) => child,
+                None => break,
+            };
+            longest = node.data.as_ref().or(longest);
+        }
+        longest
+    }
+
+    /// Look up the value stored at a key matching this one
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &self.trie;
+        for key_elem in key {
+            node = self.child(node, &key_elem)?;
+        }
+        node.data.as_ref()
+    }
+
+    /// Check whether a value is stored at a key matching this one
+    pub fn contains_key<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Count the values stored in the TrieWith
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    /// Check whether the TrieWith holds no values at all
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// The underlying Trie, with keys as they were inserted
+    pub fn as_trie(&self) -> &Trie<K, D> {
+        &self.trie
+    }
+
+    /// Consume the TrieWith, returning the underlying Trie
+    pub fn into_trie(self) -> Trie<K, D> {
+        self.trie
+    }
+
+    fn child<'a>(&self, node: &'a Trie<K, D>, key_elem: &K) -> Option<&'a Trie<K, D>> {
+        node.children.iter().find(|child| child_matches(&self.cmp, child, key_elem))
+    }
+}
+
+impl<K: PartialEq + Copy, D, C: ElementCmp<K> + Default> Default for TrieWith<K, D, C> {
+
+    fn default() -> TrieWith<K, D, C> {
+        TrieWith::with_cmp(C::default())
+    }
+}
+
+impl<K: Copy + fmt::Debug, D: fmt::Debug, C> fmt::Debug for TrieWith<K, D, C> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.trie.fmt(f)
+    }
+}
+
+fn child_matches<K, D, C: ElementCmp<K>>(cmp: &C, child: &Trie<K, D>, key_elem: &K) -> bool {
+    child.key.as_ref().is_some_and(|key| cmp.eq(key_elem, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use {Trie, TrieError};
+    use super::{CaseInsensitive, ElementCmp, Exact, TrieWith};
+
+    #[test]
+    fn test_case_insensitive() {
+        let mut t = TrieWith::with_cmp(CaseInsensitive);
+        assert_eq!(t.insert("apple".chars(), 1), Ok(()));
+        assert_eq!(t.insert("Straße".chars(), 2), Ok(()));
+
+        assert_eq!(t.get("APPLE".chars()), Some(&1));
+        assert_eq!(t.get("aPpLe".chars()), Some(&1));
+        assert_eq!(t.search("Apples".chars()), Some(&1));
+        assert_eq!(t.get("STRAßE".chars()), Some(&2));
+        assert_eq!(t.get("appl".chars()), None);
+
+        assert_eq!(t.insert("APPLE".chars(), 3), Err(TrieError::KeyAlreadyPresent));
+        // shares the stored "app" path rather than making an "APP" one beside it
+        assert_eq!(t.insert("APPly".chars(), 4), Ok(()));
+        assert_eq!(t.as_trie().get("apply".chars()), Some(&4));
+        assert_eq!(t.len(), 3);
+    }
+
+    #[test]
+    fn test_exact_matches_trie() {
+        let mut plain = Trie::new_empty();
+        let mut with: TrieWith<char, usize, Exact> = TrieWith::default();
+        for (i, word) in ["a", "ab", "abc", "B", "b"].iter().enumerate() {
+            assert_eq!(plain.insert(word.chars(), i), Ok(()));
+            assert_eq!(with.insert(word.chars(), i), Ok(()));
+        }
+        assert_eq!(with.as_trie(), &plain);
+        for query in ["", "a", "abcd", "Ab", "B", "bb", "x"].iter() {
+            assert_eq!(with.get(query.chars()), plain.get(query.chars()));
+            assert_eq!(with.search(query.chars()), plain.search(query.chars()));
+        }
+    }
+
+    #[test]
+    fn test_custom_cmp() {
+        /// Treats every digit as the same element
+        struct AnyDigit;
+
+        impl ElementCmp<char> for AnyDigit {
+            fn eq(&self, a: &char, b: &char) -> bool {
+                a == b || (a.is_ascii_digit() && b.is_ascii_digit())
+            }
+        }
+
+        let mut t = TrieWith::with_cmp(AnyDigit);
+        assert_eq!(t.insert("room 101".chars(), 1), Ok(()));
+        assert_eq!(t.get("room 247".chars()), Some(&1));
+        assert_eq!(t.get("room 24".chars()), None);
+        assert!(t.insert("room 999".chars(), 2).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use Trie;
+
+/// The differences between two Tries, from [Trie::diff]
+///
+/// Each list is in key order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrieDiff<'a, K, D: 'a> {
+    /// Entries only in the new Trie
+    pub added: Vec<(Vec<K>, &'a D)>,
+    /// Entries only in the old Trie
+    pub removed: Vec<(Vec<K>, &'a D)>,
+    /// Keys in both whose values differ, with the old value and then the new one
+    pub changed: Vec<(Vec<K>, &'a D, &'a D)>,
+}
+
+impl<'a, K: Clone, D> TrieDiff<'a, K, D> {
+
+    /// Check whether the two Tries were equal
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Copy the changes into an owned [TriePatch], which [Trie::apply_patch] can replay
+    pub fn to_patch(&self) -> TriePatch<K, D> where D: Clone {
+        TriePatch {
+            added: self.added.iter().map(|&(ref key, data)| (key.clone(), data.clone())).collect(),
+            removed: self.removed.iter().map(|(key, _)| key.clone()).collect(),
+            changed: self.changed.iter().map(|&(ref key, _, new)| (key.clone(), new.clone())).collect(),
+        }
+    }
+}
+
+/// An owned set of changes to apply to a Trie with [Trie::apply_patch]
+///
+/// Usually made from a [TrieDiff] with [TrieDiff::to_patch], but it's plain data, so it can be
+/// built by hand too, or sent elsewhere before being applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TriePatch<K, D> {
+    /// Entries to insert
+    pub added: Vec<(Vec<K>, D)>,
+    /// Keys to remove
+    pub removed: Vec<Vec<K>>,
+    /// Keys whose values should be replaced, with the new values
+    pub changed: Vec<(Vec<K>, D)>,
+}
+
+impl<K: Ord + Copy, D> Trie<K, D> {
+
+    /// Find what changed going from this Trie to `other`
+    ///
+    /// Both Tries' entries are sorted by key, then compared in a single merge pass.
+    pub fn diff<'a>(&'a self, other: &'a Trie<K, D>) -> TrieDiff<'a, K, D> where D: PartialEq {
+        let mut diff = TrieDiff {
+            added: vec![],
+            removed: vec![],
+            changed: vec![],
+        };
+        let mut old = self.to_sorted_vec().into_iter().peekable();
+        let mut new = other.to_sorted_vec().into_iter().peekable();
+        loop {
+            let order = match (old.peek(), new.peek()) {
+                (Some(old_entry), Some(new_entry)) => old_entry.0.cmp(&new_entry.0),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return diff,
+            };
+            match order {
+                Ordering::Less => diff.removed.extend(old.next()),
+                Ordering::Greater => diff.added.extend(new.next()),
+                Ordering::Equal => {
+                    let (key, old_data) = old.next().expect("peeked");
+                    let (_, new_data) = new.next().expect("peeked");
+                    if old_data != new_data {
+                        diff.changed.push((key, old_data, new_data));
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Apply a set of changes, as made by [TrieDiff::to_patch]
+    ///
+    /// Removals happen first, then additions and changes, each of which stores its value
+    /// whether or not the key is already present. So `a.apply_patch(&a.diff(&b).to_patch())`
+    /// makes `a` equal to `b`.
+    pub fn apply_patch(&mut self, patch: &TriePatch<K, D>) where D: Clone {
+        for key in &patch.removed {
+            self.remove(key.iter().cloned());
+        }
+        for (key, data) in patch.added.iter().chain(&patch.changed) {
+            self.insert_or_replace(key.iter().cloned(), data.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use Trie;
+    use super::TriePatch;
+
+    fn trie(entries: &[(&str, usize)]) -> Trie<char, usize> {
+        let mut t = Trie::new_empty();
+        for &(word, value) in entries {
+            assert_eq!(t.insert(word.chars(), value), Ok(()));
+        }
+        t
+    }
+
+    fn key(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = trie(&[("a", 1), ("ab", 2), ("abc", 3), ("b", 4)]);
+        let new = trie(&[("a", 1), ("ab", 20), ("b", 4), ("ba", 5), ("", 0)]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![(key(""), &0), (key("ba"), &5)]);
+        assert_eq!(diff.removed, vec![(key("abc"), &3)]);
+        assert_eq!(diff.changed, vec![(key("ab"), &2, &20)]);
+        assert!(!diff.is_empty());
+
+        let back = new.diff(&old);
+        assert_eq!(back.added, diff.removed);
+        assert_eq!(back.removed, diff.added);
+        assert_eq!(back.changed, vec![(key("ab"), &20, &2)]);
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let t = trie(&[("a", 1), ("ab", 2), ("b", 4)]);
+        let same = trie(&[("b", 4), ("ab", 2), ("a", 1)]);
+        assert!(t.diff(&same).is_empty());
+
+        let empty = Trie::new_empty();
+        assert!(empty.diff(&Trie::new_empty()).is_empty());
+        assert_eq!(empty.diff(&t).added.len(), 3);
+        assert_eq!(t.diff(&empty).removed.len(), 3);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let base = trie(&[("a", 1), ("ab", 2), ("abc", 3), ("b", 4)]);
+        let target = trie(&[("a", 1), ("ab", 20), ("b", 4), ("ba", 5), ("", 0)]);
+
+        let patch = base.diff(&target).to_patch();
+        assert_eq!(patch, TriePatch {
+            added: vec![(key(""), 0), (key("ba"), 5)],
+            removed: vec![key("abc")],
+            changed: vec![(key("ab"), 20)],
+        });
+        let mut patched = base.clone();
+        patched.apply_patch(&patch);
+        assert_eq!(patched, target);
+        // the removed "abc" leaves nothing behind
+        assert_eq!(patched.node_count(), target.node_count());
+
+        let mut back = target.clone();
+        back.apply_patch(&target.diff(&base).to_patch());
+        assert_eq!(back, base);
+
+        let mut unchanged = base.clone();
+        unchanged.apply_patch(&base.diff(&base).to_patch());
+        assert_eq!(unchanged, base);
+    }
+}
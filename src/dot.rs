@@ -0,0 +1,131 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Write};
+
+use {Trie, TrieVisitor};
+
+impl<K: PartialEq + Copy + Display, D: Display> Trie<K, D> {
+
+    /// Render the structure of the Trie as a GraphViz `digraph`
+    ///
+    /// Every node is labelled with its key element, nodes holding a value are drawn as double
+    /// circles with the value added to the label, and edges run from parents to children.
+    pub fn to_dot(&self) -> String {
+        let mut dot = DotWriter {
+            out: String::from("digraph trie {\n    node [shape=circle];\n"),
+            path: vec![0],
+            next_id: 1,
+            pending: Some((String::new(), false)),
+        };
+        self.walk(&mut dot);
+        dot.flush();
+        dot.out.push_str("}\n");
+        dot.out
+    }
+}
+
+/// A [TrieVisitor] writing a DOT node for each node the walk enters, and an edge to it once
+/// it's left
+struct DotWriter {
+    out: String,
+    /// the ids of the nodes from the root (always 0) down to the current one
+    path: Vec<usize>,
+    next_id: usize,
+    /// the current node's label, and whether it holds a value, until it's known whether it
+    /// does
+    pending: Option<(String, bool)>,
+}
+
+impl DotWriter {
+
+    /// Write out the current node, if it hasn't been already
+    fn flush(&mut self) {
+        if let Some((label, has_value)) = self.pending.take() {
+            let id = *self.path.last().expect("the root is always on the path");
+            let shape = if has_value { ", shape=doublecircle" } else { "" };
+            writeln!(self.out, "    n{} [label=\"{}\"{}];", id, escape(&label), shape)
+                .expect("writing to a String can't fail");
+        }
+    }
+}
+
+impl<K: Display, D: Display> TrieVisitor<K, D> for DotWriter {
+
+    fn enter(&mut self, key: &K, _depth: usize) {
+        self.flush();
+        self.path.push(self.next_id);
+        self.next_id += 1;
+        self.pending = Some((format!("{}", key), false));
+    }
+
+    fn visit_value(&mut self, _depth: usize, data: &D) {
+        if let Some((ref mut label, ref mut has_value)) = self.pending {
+            write!(label, ": {}", data).expect("writing to a String can't fail");
+            *has_value = true;
+        }
+    }
+
+    fn leave(&mut self, _key: &K, _depth: usize) {
+        self.flush();
+        let id = self.path.pop().expect("every node left was entered");
+        let parent = *self.path.last().expect("the root is never left");
+        writeln!(self.out, "    n{} -> n{};", parent, id).expect("writing to a String can't fail");
+    }
+}
+
+/// Escape a label for use inside a double-quoted DOT string
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        if c == '"' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+
+    #[test]
+    fn test_to_dot() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ac".chars(), 2), Ok(()));
+        assert_eq!(t.insert("a".chars(), 3), Ok(()));
+
+        let dot = t.to_dot();
+        assert!(dot.starts_with("digraph trie {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("n0 [label=\"\"];"));
+        assert!(dot.contains("n1 [label=\"a: 3\", shape=doublecircle];"));
+        assert!(dot.contains("n2 [label=\"b: 1\", shape=doublecircle];"));
+        assert!(dot.contains("n3 [label=\"c: 2\", shape=doublecircle];"));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n1 -> n3;"));
+        assert_eq!(dot.matches("->").count(), 3);
+    }
+
+    #[test]
+    fn test_to_dot_escapes_labels() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("\"".chars(), "a \\ b"), Ok(()));
+
+        let dot = t.to_dot();
+        assert!(dot.contains("n1 [label=\"\\\": a \\\\ b\", shape=doublecircle];"));
+    }
+
+    #[test]
+    fn test_to_dot_very_long_key() {
+        let len = 100_000;
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert((0..len).map(|i| i % 7), "long"), Ok(()));
+
+        let dot = t.to_dot();
+        assert_eq!(dot.matches("->").count(), len);
+        let deepest = format!("n{} [label=\"{}: long\", shape=doublecircle];", len, (len - 1) % 7);
+        assert!(dot.contains(&deepest));
+    }
+}
@@ -0,0 +1,326 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use Trie;
+
+/// `check` of a slot no state occupies
+const FREE: u32 = u32::MAX;
+/// `check` of the root's slot, which has no parent
+const ROOT: u32 = u32::MAX - 1;
+/// `values` of a state with no value
+const NO_VALUE: u32 = u32::MAX;
+
+/// An immutable double-array trie over byte keys, for large static dictionaries
+///
+/// Built with [Trie::build_double_array]. Each state is a slot in two parallel arrays: the
+/// child of state `s` reached by byte `c` is the slot `t = base[s] + c`, and it exists only if
+/// `check[t] == s`. A lookup is then one addition and one comparison per key byte, with no
+/// searching at all, at the cost of some unused slots between the packed child sets.
+#[derive(Clone, Debug)]
+pub struct DoubleArrayTrie<D> {
+    base: Vec<u32>,
+    check: Vec<u32>,
+    /// index into `data` of each state's value, or NO_VALUE
+    values: Vec<u32>,
+    data: Vec<D>,
+}
+
+impl<D: Clone> Trie<u8, D> {
+
+    /// Compile the Trie into an immutable [DoubleArrayTrie]
+    ///
+    /// Every node's children have to be fitted into free slots, so this is for dictionaries
+    /// built once and queried many times. Values are cloned; the Trie is left untouched.
+    pub fn build_double_array(&self) -> DoubleArrayTrie<D> {
+        DoubleArrayTrie::from_trie(self)
+    }
+}
+
+/// No slot: the end of the candidate list
+const NONE: u32 = u32::MAX;
+/// How many times a free slot may fail to fit a node's first child before it's given up on
+const MAX_FAILS: u8 = 16;
+
+/// Working state while fitting states into slots
+///
+/// Free slots are kept in a doubly-linked candidate list, so the search for a base only visits
+/// slots where the first child could go instead of scanning every slot. A slot that keeps
+/// failing to fit is dropped from the list (it stays free, and can still take a later child),
+/// so the crowded front of the array isn't searched over and over.
+struct Builder<D> {
+    da: DoubleArrayTrie<D>,
+    next: Vec<u32>,
+    prev: Vec<u32>,
+    /// failed fits per slot; only meaningful while the slot is a candidate
+    fails: Vec<u8>,
+    in_list: Vec<bool>,
+    head: u32,
+    tail: u32,
+}
+
+impl<D: Clone> DoubleArrayTrie<D> {
+
+    fn from_trie(trie: &Trie<u8, D>) -> DoubleArrayTrie<D> {
+        let mut builder = Builder {
+            da: DoubleArrayTrie {
+                base: vec![0],
+                check: vec![ROOT],
+                values: vec![NO_VALUE],
+                data: vec![],
+            },
+            next: vec![NONE],
+            prev: vec![NONE],
+            fails: vec![0],
+            in_list: vec![false],
+            head: NONE,
+            tail: NONE,
+        };
+        let mut queue = VecDeque::new();
+        queue.push_back((trie, 0));
+        while let Some((node, state)) = queue.pop_front() {
+            if let Some(ref data) = node.data {
+                let da = &mut builder.da;
+                assert!(da.data.len() < NO_VALUE as usize, "too many values to build a double array");
+                da.values[state] = da.data.len() as u32;
+                da.data.push(data.clone());
+            }
+            if node.children.is_empty() {
+                continue;
+            }
+
+            let mut children: Vec<(usize, &Trie<u8, D>)> = node.children
+                .iter()
+                .map(|child| (child.key.expect("child nodes always have a key") as usize, child))
+                .collect();
+            children.sort_unstable_by_key(|&(label, _)| label);
+            let labels: Vec<usize> = children.iter().map(|&(label, _)| label).collect();
+            let base = builder.find_base(&labels);
+            builder.da.base[state] = base as u32;
+            for (label, child) in children {
+                builder.occupy(base + label, state);
+                queue.push_back((child, base + label));
+            }
+        }
+        let mut da = builder.da;
+        da.shrink();
+        da
+    }
+
+    /// Drop the unused slots past the last state
+    fn shrink(&mut self) {
+        let used = self.check.iter().rposition(|&check| check != FREE).map_or(0, |last| last + 1);
+        self.base.truncate(used);
+        self.check.truncate(used);
+        self.values.truncate(used);
+        self.base.shrink_to_fit();
+        self.check.shrink_to_fit();
+        self.values.shrink_to_fit();
+    }
+}
+
+impl<D> Builder<D> {
+
+    /// Find a base which puts every one of the (sorted) `labels` in a free slot, growing the
+    /// arrays so that every slot it could reach exists
+    fn find_base(&mut self, labels: &[usize]) -> usize {
+        let mut slot = self.head;
+        loop {
+            if slot == NONE {
+                // out of candidates, so add more slots on the end and carry on from them
+                let len = self.da.check.len();
+                self.grow(len + 256);
+                slot = len as u32;
+                continue;
+            }
+            let candidate = slot as usize;
+            let next = self.next[candidate];
+            // bases start at 1, so no child can land on the root
+            if candidate > labels[0] {
+                let base = candidate - labels[0];
+                let check = &self.da.check;
+                let fits = labels[1..].iter().all(|&label| {
+                    check.get(base + label).is_none_or(|&check| check == FREE)
+                });
+                if fits {
+                    self.grow(base + 256);
+                    return base;
+                }
+            }
+            self.fails[candidate] += 1;
+            if self.fails[candidate] >= MAX_FAILS {
+                self.unlink(candidate);
+            }
+            slot = next;
+        }
+    }
+
+    /// Mark `slot` as the child state of `parent`
+    fn occupy(&mut self, slot: usize, parent: usize) {
+        self.da.check[slot] = parent as u32;
+        if self.in_list[slot] {
+            self.unlink(slot);
+        }
+    }
+
+    /// Extend the arrays to at least `len` slots, all of them free candidates
+    fn grow(&mut self, len: usize) {
+        let old_len = self.da.check.len();
+        if len <= old_len {
+            return;
+        }
+        assert!(len < ROOT as usize, "too many nodes to build a double array");
+        self.da.base.resize(len, 0);
+        self.da.check.resize(len, FREE);
+        self.da.values.resize(len, NO_VALUE);
+        self.next.resize(len, NONE);
+        self.prev.resize(len, NONE);
+        self.fails.resize(len, 0);
+        self.in_list.resize(len, true);
+        for slot in old_len..len {
+            self.prev[slot] = self.tail;
+            match self.tail {
+                NONE => self.head = slot as u32,
+                tail => self.next[tail as usize] = slot as u32,
+            }
+            self.tail = slot as u32;
+        }
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.prev[slot], self.next[slot]);
+        match prev {
+            NONE => self.head = next,
+            prev => self.next[prev as usize] = next,
+        }
+        match next {
+            NONE => self.tail = prev,
+            next => self.prev[next as usize] = prev,
+        }
+        self.in_list[slot] = false;
+    }
+}
+
+impl<D> DoubleArrayTrie<D> {
+
+    /// Look up the value stored at exactly this key
+    pub fn get(&self, key: &[u8]) -> Option<&D> {
+        let mut state = 0;
+        for &key_elem in key {
+            state = self.child(state, key_elem)?;
+        }
+        self.value(state)
+    }
+
+    /// Search for the longest stored prefix of the key
+    pub fn search(&self, key: &[u8]) -> Option<&D> {
+        let mut state = 0;
+        let mut longest = self.value(0);
+        for &key_elem in key {
+            state = match self.child(state, key_elem) {
+                Some(child) => child,
+                None => break,
+            };
+            longest = self.value(state).or(longest);
+        }
+        longest
+    }
+
+    /// Check whether a value is stored at exactly this key
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The number of values stored
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Follow the transition from `state` on `key_elem`, if there is one
+    fn child(&self, state: usize, key_elem: u8) -> Option<usize> {
+        let slot = self.base[state] as usize + key_elem as usize;
+        match self.check.get(slot) {
+            Some(&check) if check == state as u32 => Some(slot),
+            _ => None,
+        }
+    }
+
+    fn value(&self, state: usize) -> Option<&D> {
+        match self.values[state] {
+            NO_VALUE => None,
+            index => Some(&self.data[index as usize]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use Trie;
+    use super::FREE;
+
+    fn build() -> Trie<u8, usize> {
+        let mut t = Trie::new_empty();
+        let words: [&[u8]; 12] = [b"", b"a", b"to", b"tea", b"ted", b"ten", b"i", b"in", b"inn",
+                                  b"tenth", b"\x00\xff", b"\xff"];
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.iter().cloned(), i), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_double_array_matches_trie() {
+        let t = build();
+        let da = t.build_double_array();
+        assert_eq!(da.len(), t.len());
+
+        let queries: [&[u8]; 20] = [b"", b"a", b"ab", b"t", b"to", b"tea", b"teas", b"te", b"ten",
+                                    b"tent", b"tenth", b"tenths", b"i", b"inn", b"inns", b"q",
+                                    b"\x00", b"\x00\xff", b"\xff", b"\xff\xff"];
+        for query in queries.iter() {
+            assert_eq!(da.get(query), t.get(query.iter().cloned()), "get {:?}", query);
+            assert_eq!(da.search(query), t.search(query.iter().cloned()), "search {:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_double_array_many_keys() {
+        // enough keys, with enough shared prefixes, that child sets have to interleave
+        let mut t = Trie::new_empty();
+        let mut state: u32 = 12345;
+        for i in 0..2000 {
+            let key: Vec<u8> = (0..1 + i % 7).map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8 % 40
+            }).collect();
+            t.insert(key, i).ok();
+        }
+        let da = t.build_double_array();
+        assert_eq!(da.len(), t.len());
+        for (key, value) in t.iter() {
+            assert_eq!(da.get(&key), Some(value));
+            let mut longer = key.clone();
+            longer.push(200);
+            assert_eq!(da.get(&longer), None);
+        }
+        // states are packed: there are no more slots than a few times the node count
+        let used = da.check.iter().filter(|&&check| check != FREE).count();
+        assert_eq!(used, t.node_count() + 1);
+        assert!(da.check.len() < 4 * used + 256);
+    }
+
+    #[test]
+    fn test_double_array_empty() {
+        let t: Trie<u8, i32> = Trie::new_empty();
+        let da = t.build_double_array();
+        assert!(da.is_empty());
+        assert_eq!(da.get(b""), None);
+        assert_eq!(da.search(b"ab"), None);
+        assert!(!da.contains_key(b"a"));
+    }
+}
@@ -0,0 +1,265 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use Trie;
+
+/// A view into a single key of a [Trie], which may either be vacant or occupied
+///
+/// Constructed by [Trie::entry], and modelled on `std::collections::hash_map::Entry`
+pub enum Entry<'a, K: 'a, D: 'a> {
+    /// A key which already holds a value
+    Occupied(OccupiedEntry<'a, K, D>),
+    /// A key which holds no value (and may not have any nodes yet)
+    Vacant(VacantEntry<'a, K, D>),
+}
+
+/// An occupied entry
+///
+/// Holds the whole Trie and the path of child indices down to the value, so that removing it
+/// can prune the nodes it leaves leading nowhere.
+pub struct OccupiedEntry<'a, K: 'a, D: 'a> {
+    trie: &'a mut Trie<K, D>,
+    key: Vec<K>,
+    indices: Vec<usize>,
+}
+
+/// A vacant entry
+///
+/// Holds the path of child indices along as much of the key as the Trie already has; the
+/// rest of the key still needs nodes created for it. Nothing is allocated until a value is
+/// inserted.
+pub struct VacantEntry<'a, K: 'a, D: 'a> {
+    trie: &'a mut Trie<K, D>,
+    key: Vec<K>,
+    indices: Vec<usize>,
+}
+
+impl<'a, K: PartialEq + Copy, D> Entry<'a, K, D> {
+
+    pub(crate) fn occupied(trie: &'a mut Trie<K, D>, key: Vec<K>, indices: Vec<usize>)
+                           -> Entry<'a, K, D> {
+        Entry::Occupied(OccupiedEntry { trie, key, indices })
+    }
+
+    pub(crate) fn vacant(trie: &'a mut Trie<K, D>, key: Vec<K>, indices: Vec<usize>)
+                         -> Entry<'a, K, D> {
+        Entry::Vacant(VacantEntry { trie, key, indices })
+    }
+
+    /// The key this entry is for
+    pub fn key(&self) -> &[K] {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, and return a mutable reference to the value
+    pub fn or_insert(self, default: D) -> &'a mut D {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Insert the result of `default` if the entry is vacant, and return a mutable reference
+    /// to the value
+    pub fn or_insert_with<G: FnOnce() -> D>(self, default: G) -> &'a mut D {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Run `f` on the value if the entry is occupied
+    pub fn and_modify<G: FnOnce(&mut D)>(self, f: G) -> Entry<'a, K, D> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            },
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D: Default> Entry<'a, K, D> {
+
+    /// Insert `D::default()` if the entry is vacant, and return a mutable reference to the value
+    pub fn or_default(self) -> &'a mut D {
+        self.or_insert_with(D::default)
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D> OccupiedEntry<'a, K, D> {
+
+    /// The key this entry is for
+    pub fn key(&self) -> &[K] {
+        &self.key
+    }
+
+    /// Get a reference to the value
+    pub fn get(&self) -> &D {
+        let mut node = &*self.trie;
+        for &index in &self.indices {
+            node = &node.children[index];
+        }
+        node.data.as_ref().expect("occupied entry without data")
+    }
+
+    /// Get a mutable reference to the value
+    ///
+    /// Use [OccupiedEntry::into_mut] if the reference needs to outlive the entry
+    pub fn get_mut(&mut self) -> &mut D {
+        let mut node = &mut *self.trie;
+        for &index in &self.indices {
+            node = &mut node.children[index];
+        }
+        node.data.as_mut().expect("occupied entry without data")
+    }
+
+    /// Convert the entry into a mutable reference to the value
+    pub fn into_mut(self) -> &'a mut D {
+        let mut node = self.trie;
+        for &index in &self.indices {
+            node = &mut node.children[index];
+        }
+        node.data.as_mut().expect("occupied entry without data")
+    }
+
+    /// Replace the value, returning the old one
+    pub fn insert(&mut self, data: D) -> D {
+        mem::replace(self.get_mut(), data)
+    }
+
+    /// Remove the value, returning it
+    ///
+    /// Nodes left leading nowhere are pruned, as [Trie::remove] does.
+    pub fn remove(self) -> D {
+        self.remove_entry().1
+    }
+
+    /// Remove the value, returning it along with its key
+    pub fn remove_entry(self) -> (Vec<K>, D) {
+        let data = self.trie.remove_path(&self.indices).expect("occupied entry without data");
+        (self.key, data)
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D> VacantEntry<'a, K, D> {
+
+    /// The key this entry is for
+    pub fn key(&self) -> &[K] {
+        &self.key
+    }
+
+    /// Take ownership of the key
+    pub fn into_key(self) -> Vec<K> {
+        self.key
+    }
+
+    /// Store a value at this key, creating any missing nodes along the way, and return a
+    /// mutable reference to it
+    pub fn insert(self, data: D) -> &'a mut D {
+        let mut node = self.trie;
+        for &index in &self.indices {
+            node = &mut node.children[index];
+        }
+        for &key_elem in &self.key[self.indices.len()..] {
+            node = node.child_or_insert(key_elem);
+        }
+        node.data.get_or_insert(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use Trie;
+    use super::Entry;
+
+    #[test]
+    fn test_entry_vacant() {
+        let mut t: Trie<char, i32> = Trie::new_empty();
+
+        match t.entry("abc".chars()) {
+            Entry::Vacant(entry) => { *entry.insert(1) += 1; },
+            Entry::Occupied(_) => panic!("expected a vacant entry"),
+        }
+        assert_eq!(t.get("abc".chars()), Some(&2));
+
+        // a vacant entry on a prefix of an existing key reuses the existing nodes
+        assert_eq!(*t.entry("ab".chars()).or_insert(5), 5);
+        assert_eq!(t.get("ab".chars()), Some(&5));
+        assert_eq!(t.children.len(), 1);
+        assert_eq!(t.children[0].children.len(), 1);
+
+        // and one extending an existing key grows from its node
+        assert_eq!(*t.entry("abcd".chars()).or_insert_with(|| 7), 7);
+        assert_eq!(t.get("abcd".chars()), Some(&7));
+        assert_eq!(t.get("abc".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_entry_occupied() {
+        let mut t: Trie<char, i32> = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 2), Ok(()));
+
+        match t.entry("ab".chars()) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(*entry.get(), 1);
+                assert_eq!(entry.insert(10), 1);
+            },
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert_eq!(t.get("ab".chars()), Some(&10));
+
+        assert_eq!(*t.entry("abc".chars()).or_insert(100), 2);
+        t.entry("abc".chars()).and_modify(|v| *v += 1).or_insert(100);
+        assert_eq!(t.get("abc".chars()), Some(&3));
+    }
+
+    #[test]
+    fn test_entry_or_default() {
+        let mut t: Trie<char, Vec<i32>> = Trie::new_empty();
+        t.entry("ab".chars()).or_default().push(1);
+        t.entry("ab".chars()).or_default().push(2);
+        assert_eq!(t.get("ab".chars()), Some(&vec![1, 2]));
+    }
+
+    #[test]
+    fn test_entry_key_and_remove() {
+        let mut t: Trie<char, i32> = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abcd".chars(), 2), Ok(()));
+
+        let entry = t.entry("abc".chars());
+        assert_eq!(entry.key(), &['a', 'b', 'c']);
+        match entry {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &['a', 'b', 'c']);
+                assert_eq!(entry.into_key(), vec!['a', 'b', 'c']);
+            },
+            Entry::Occupied(_) => panic!("expected a vacant entry"),
+        }
+        assert_eq!(t.node_count(), 4);
+
+        match t.entry("abcd".chars()) {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.key(), &['a', 'b', 'c', 'd']);
+                assert_eq!(entry.remove_entry(), (vec!['a', 'b', 'c', 'd'], 2));
+            },
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        // the emptied "c" and "d" nodes are pruned, as for Trie::remove
+        assert_eq!(t.node_count(), 2);
+
+        match t.entry("ab".chars()) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), 1),
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert!(t.is_empty());
+        assert_eq!(t.node_count(), 0);
+    }
+}
@@ -0,0 +1,30 @@
+use core::hash::Hasher;
+
+/// FNV-1a, a fast hasher with a fixed seed
+///
+/// Quicker than the default SipHash for short keys. Nothing hashed with it is exposed to
+/// crafted collisions, so there's little for SipHash's resistance to protect. Being seeded the
+/// same every time also makes it usable where each entry of a Trie is hashed on its own.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+
+    fn default() -> FnvHasher {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
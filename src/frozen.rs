@@ -0,0 +1,132 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::mem;
+
+use Trie;
+
+/// An immutable, flattened form of a [Trie] for read-heavy use
+///
+/// Built with [Trie::freeze]. Nodes are laid out breadth-first in flat arrays, so each node's
+/// children sit next to each other (in key order) and are found by binary search over a
+/// contiguous slice, rather than by chasing a `Vec` per node.
+#[derive(Clone, Debug)]
+pub struct FrozenTrie<K, D> {
+    /// the children of node `n` are the nodes `children[n].0 .. children[n].1`
+    children: Vec<(u32, u32)>,
+    /// the key element leading to node `n` is `labels[n - 1]`; the root has none
+    labels: Vec<K>,
+    data: Vec<Option<D>>,
+}
+
+impl<K: Ord + Copy, D> FrozenTrie<K, D> {
+
+    pub(crate) fn from_trie(trie: Trie<K, D>) -> FrozenTrie<K, D> {
+        let mut frozen = FrozenTrie {
+            children: vec![],
+            labels: vec![],
+            data: vec![],
+        };
+        // nodes are numbered in the order they're queued, which is also the order they're
+        // popped, so every node's children get consecutive ids
+        let mut queue = VecDeque::new();
+        queue.push_back(trie);
+        let mut next_id: usize = 1;
+        while let Some(mut node) = queue.pop_front() {
+            let mut children = mem::take(&mut node.children);
+            children.sort_unstable_by_key(|child| child.key);
+            let first_child = next_id;
+            next_id += children.len();
+            assert!(next_id <= u32::MAX as usize, "too many nodes to freeze");
+            frozen.children.push((first_child as u32, next_id as u32));
+            frozen.data.push(node.data.take());
+            for child in children {
+                frozen.labels.push(child.key.expect("child nodes always have a key"));
+                queue.push_back(child);
+            }
+        }
+        frozen
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = 0;
+        for key_elem in key {
+            node = self.child(node, key_elem)?;
+        }
+        self.data[node].as_ref()
+    }
+
+    /// Search for the longest match in the FrozenTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = 0;
+        let mut longest = self.data[0].as_ref();
+        for key_elem in key {
+            node = match self.child(node, key_elem) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(ref data) = self.data[node] {
+                longest = Some(data);
+            }
+        }
+        longest
+    }
+
+    /// Find the id of the child of `node` reached by `key_elem`
+    fn child(&self, node: usize, key_elem: K) -> Option<usize> {
+        let (start, end) = self.children[node];
+        let (start, end) = (start as usize, end as usize);
+        self.labels[start - 1..end - 1]
+            .binary_search(&key_elem)
+            .ok()
+            .map(|index| start + index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+
+    fn build() -> Trie<char, usize> {
+        let mut t = Trie::new_empty();
+        let words = ["", "a", "to", "tea", "ted", "ten", "i", "in", "inn", "tenth", "zebra"];
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_frozen_matches_trie() {
+        let t = build();
+        let frozen = build().freeze();
+
+        let queries = ["", "a", "ab", "t", "to", "tea", "teas", "te", "ten", "tent", "tenth",
+                       "tenths", "i", "inn", "inns", "z", "zebra", "zebras", "q"];
+        for query in queries.iter() {
+            assert_eq!(frozen.get(query.chars()), t.get(query.chars()), "get {}", query);
+            assert_eq!(frozen.search(query.chars()), t.search(query.chars()), "search {}", query);
+        }
+    }
+
+    #[test]
+    fn test_frozen_layout() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("c".chars(), 2), Ok(()));
+
+        let frozen = t.freeze();
+        // root, then 'a' and 'c', then 'b'
+        assert_eq!(frozen.labels, vec!['a', 'c', 'b']);
+        assert_eq!(frozen.children, vec![(1, 3), (3, 4), (4, 4), (4, 4)]);
+        assert_eq!(frozen.data, vec![None, None, Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn test_frozen_empty() {
+        let t: Trie<u8, i32> = Trie::new_empty();
+        let frozen = t.freeze();
+        assert_eq!(frozen.get(vec![]), None);
+        assert_eq!(frozen.search(vec![1, 2]), None);
+    }
+}
@@ -0,0 +1,241 @@
+use alloc::vec::Vec;
+
+use Trie;
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Find every stored key within `max_distance` edits of the given key
+    ///
+    /// Edits are single-element insertions, deletions and substitutions (Levenshtein
+    /// distance). The Trie is walked once, keeping one row of the edit-distance table per node,
+    /// and any subtree whose row can no longer get within `max_distance` is skipped.
+    pub fn fuzzy_search<F: IntoIterator<Item=K>>(&self, key: F, max_distance: usize)
+                                                -> Vec<(Vec<K>, &D)> {
+        let query: Vec<K> = key.into_iter().collect();
+        // distance from the empty key to each prefix of the query
+        let first_row: Vec<usize> = (0..query.len() + 1).collect();
+
+        let mut found = vec![];
+        if let Some(ref data) = self.data {
+            if query.len() <= max_distance {
+                found.push((vec![], data));
+            }
+        }
+        let mut path = vec![];
+        for child in &self.children {
+            child.fuzzy_walk(&query, &first_row, max_distance, &mut path, &mut found);
+        }
+        found
+    }
+
+    fn fuzzy_walk<'a>(&'a self, query: &[K], prev_row: &[usize], max_distance: usize,
+                      path: &mut Vec<K>, found: &mut Vec<(Vec<K>, &'a D)>) {
+        let key_elem = self.key.expect("child nodes always have a key");
+        let row = levenshtein_row(query, prev_row, key_elem);
+
+        path.push(key_elem);
+        if let Some(ref data) = self.data {
+            if row[query.len()] <= max_distance {
+                found.push((path.clone(), data));
+            }
+        }
+        // extending the key can't bring the distance below the row's minimum
+        if row.iter().min().is_some_and(|&min| min <= max_distance) {
+            for child in &self.children {
+                child.fuzzy_walk(query, &row, max_distance, path, found);
+            }
+        }
+        path.pop();
+    }
+
+    /// Find every stored key within `max_distance` edits of the given key, counting swapped
+    /// neighbours as one edit
+    ///
+    /// Like [self.fuzzy_search], but a transposition of two adjacent elements costs 1 rather
+    /// than 2 (the restricted Damerau-Levenshtein, or optimal string alignment, distance), which
+    /// is closer to how people mistype. Each node needs its parent's row as well as its own to
+    /// spot a swap, but no row can fall below its parent's minimum, so the same pruning holds.
+    pub fn fuzzy_search_damerau<F: IntoIterator<Item=K>>(&self, key: F, max_distance: usize)
+                                                        -> Vec<(Vec<K>, &D)> {
+        let query: Vec<K> = key.into_iter().collect();
+        let first_row: Vec<usize> = (0..query.len() + 1).collect();
+
+        let mut found = vec![];
+        if let Some(ref data) = self.data {
+            if query.len() <= max_distance {
+                found.push((vec![], data));
+            }
+        }
+        let mut path = vec![];
+        for child in &self.children {
+            child.damerau_walk(&query, &first_row, &[], max_distance, &mut path, &mut found);
+        }
+        found
+    }
+
+    fn damerau_walk<'a>(&'a self, query: &[K], prev_row: &[usize], prev_prev_row: &[usize],
+                        max_distance: usize, path: &mut Vec<K>, found: &mut Vec<(Vec<K>, &'a D)>) {
+        let key_elem = self.key.expect("child nodes always have a key");
+        let mut row = levenshtein_row(query, prev_row, key_elem);
+        if let Some(&prev_elem) = path.last() {
+            for i in 1..query.len() {
+                if query[i] == prev_elem && query[i - 1] == key_elem && query[i] != key_elem {
+                    row[i + 1] = row[i + 1].min(prev_prev_row[i - 1] + 1);
+                }
+            }
+        }
+
+        path.push(key_elem);
+        if let Some(ref data) = self.data {
+            if row[query.len()] <= max_distance {
+                found.push((path.clone(), data));
+            }
+        }
+        if row.iter().min().is_some_and(|&min| min <= max_distance) {
+            for child in &self.children {
+                child.damerau_walk(query, &row, prev_row, max_distance, path, found);
+            }
+        }
+        path.pop();
+    }
+
+    /// Find the stored key closest to the given key by edit distance
+    ///
+    /// Returns the key along with its Levenshtein distance from the query and its value, or
+    /// `None` if the Trie is empty. The best distance found so far bounds the rest of the walk,
+    /// so subtrees that can't beat it are skipped; on a tie the first key in depth-first order
+    /// wins.
+    pub fn nearest<F: IntoIterator<Item=K>>(&self, key: F) -> Option<(Vec<K>, usize, &D)> {
+        let query: Vec<K> = key.into_iter().collect();
+        let first_row: Vec<usize> = (0..query.len() + 1).collect();
+
+        let mut best = self.data.as_ref().map(|data| (vec![], query.len(), data));
+        let mut path = vec![];
+        for child in &self.children {
+            child.nearest_walk(&query, &first_row, &mut path, &mut best);
+        }
+        best
+    }
+
+    fn nearest_walk<'a>(&'a self, query: &[K], prev_row: &[usize], path: &mut Vec<K>,
+                        best: &mut Option<(Vec<K>, usize, &'a D)>) {
+        let key_elem = self.key.expect("child nodes always have a key");
+        let row = levenshtein_row(query, prev_row, key_elem);
+
+        path.push(key_elem);
+        if let Some(ref data) = self.data {
+            let distance = row[query.len()];
+            if best.as_ref().is_none_or(|&(_, best_distance, _)| distance < best_distance) {
+                *best = Some((path.clone(), distance, data));
+            }
+        }
+        let min = row.iter().min().cloned().unwrap_or(0);
+        if best.as_ref().is_none_or(|&(_, best_distance, _)| min < best_distance) {
+            for child in &self.children {
+                child.nearest_walk(query, &row, path, best);
+            }
+        }
+        path.pop();
+    }
+}
+
+/// Compute the next row of the edit-distance table, after appending `key_elem` to a key
+fn levenshtein_row<K: PartialEq>(query: &[K], prev_row: &[usize], key_elem: K) -> Vec<usize> {
+    let mut row = Vec::with_capacity(prev_row.len());
+    row.push(prev_row[0] + 1);
+    for (i, query_elem) in query.iter().enumerate() {
+        let substitute = prev_row[i] + if *query_elem == key_elem { 0 } else { 1 };
+        let delete = prev_row[i + 1] + 1;
+        let insert = row[i] + 1;
+        row.push(substitute.min(delete).min(insert));
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use Trie;
+
+    fn dictionary() -> Trie<char, usize> {
+        let mut t = Trie::new_empty();
+        let words = ["spelling", "spilling", "selling", "dwelling", "spell", "apple"];
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        t
+    }
+
+    fn words(found: Vec<(Vec<char>, &usize)>) -> Vec<String> {
+        found.into_iter().map(|(k, _)| k.into_iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let t = dictionary();
+
+        assert_eq!(words(t.fuzzy_search("speling".chars(), 1)), vec!["spelling"]);
+        assert_eq!(words(t.fuzzy_search("speling".chars(), 2)), vec!["spelling", "spilling", "selling"]);
+        assert_eq!(words(t.fuzzy_search("apple".chars(), 0)), vec!["apple"]);
+        assert_eq!(words(t.fuzzy_search("aple".chars(), 0)), Vec::<String>::new());
+        assert_eq!(t.fuzzy_search("spel".chars(), 1), vec![("spell".chars().collect(), &4)]);
+    }
+
+    #[test]
+    fn test_fuzzy_search_empty_key() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("".chars(), 0), Ok(()));
+        assert_eq!(t.insert("a".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 2), Ok(()));
+
+        assert_eq!(t.fuzzy_search("".chars(), 1), vec![(vec![], &0), (vec!['a'], &1)]);
+        assert_eq!(t.fuzzy_search("b".chars(), 1).len(), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_search_damerau() {
+        let mut t = Trie::new_empty();
+        let entries = ["the", "then", "ten", "tea"];
+        for (i, word) in entries.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert_eq!(words(t.fuzzy_search_damerau("teh".chars(), 1)), vec!["the", "ten", "tea"]);
+        assert_eq!(words(t.fuzzy_search("teh".chars(), 1)), vec!["ten", "tea"]);
+        assert_eq!(words(t.fuzzy_search("teh".chars(), 2)), vec!["the", "then", "ten", "tea"]);
+        assert_eq!(words(t.fuzzy_search_damerau("hte".chars(), 1)), vec!["the"]);
+        // two swaps
+        assert_eq!(words(t.fuzzy_search_damerau("htne".chars(), 2)), vec!["the", "then", "ten"]);
+        assert!(!words(t.fuzzy_search("htne".chars(), 2)).contains(&"then".to_string()));
+        assert_eq!(words(t.fuzzy_search_damerau("htne".chars(), 1)), Vec::<String>::new());
+
+        // without swaps it's the same search
+        let d = dictionary();
+        for max_distance in 0..3 {
+            assert_eq!(d.fuzzy_search_damerau("speling".chars(), max_distance),
+                       d.fuzzy_search("speling".chars(), max_distance));
+        }
+        assert_eq!(t.fuzzy_search_damerau("".chars(), 3).len(), 3);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let t = dictionary();
+
+        let (key, distance, &value) = t.nearest("speling".chars()).unwrap();
+        assert_eq!(key.into_iter().collect::<String>(), "spelling");
+        assert_eq!((distance, value), (1, 0));
+
+        let (key, distance, _) = t.nearest("dweling".chars()).unwrap();
+        assert_eq!(key.into_iter().collect::<String>(), "dwelling");
+        assert_eq!(distance, 1);
+
+        assert_eq!(t.nearest("apple".chars()), Some(("apple".chars().collect(), 0, &5)));
+        // "spilling" and "spelling" are both one edit away; depth-first order picks "spelling"
+        assert_eq!(t.nearest("spxlling".chars()).map(|(_, d, &v)| (d, v)), Some((1, 0)));
+
+        let empty: Trie<char, usize> = Trie::new_empty();
+        assert_eq!(empty.nearest("apple".chars()), None);
+    }
+}
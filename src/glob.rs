@@ -0,0 +1,134 @@
+use alloc::vec::Vec;
+
+use Trie;
+
+/// One element of a pattern for [Trie::glob_search]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlobElem<K> {
+    /// Matches exactly this key element
+    Exact(K),
+    /// Matches any single key element, like `?` in a shell glob
+    Any,
+    /// Matches any run of key elements, including none, like `*` in a shell glob
+    AnySeq,
+}
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Find every stored key matching a glob-style pattern, depth-first
+    ///
+    /// The pattern is run like a little NFA: each node is visited at most once, carrying the
+    /// set of pattern positions its path could have reached, and a subtree is skipped as soon
+    /// as that set is empty. So a key shows up once however many ways the pattern matches it.
+    pub fn glob_search(&self, pattern: &[GlobElem<K>]) -> Vec<(Vec<K>, &D)> {
+        let mut states = vec![false; pattern.len() + 1];
+        states[0] = true;
+        glob_closure(pattern, &mut states);
+
+        let mut found = vec![];
+        let mut path = vec![];
+        self.glob_walk(pattern, &states, &mut path, &mut found);
+        found
+    }
+
+    fn glob_walk<'a>(&'a self, pattern: &[GlobElem<K>], states: &[bool], path: &mut Vec<K>,
+                     found: &mut Vec<(Vec<K>, &'a D)>) {
+        if let Some(ref data) = self.data {
+            if states[pattern.len()] {
+                found.push((path.clone(), data));
+            }
+        }
+        for child in &self.children {
+            let key_elem = child.key.expect("child nodes always have a key");
+            let next = glob_step(pattern, states, key_elem);
+            if next.iter().any(|&state| state) {
+                path.push(key_elem);
+                child.glob_walk(pattern, &next, path, found);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// Advance every live pattern position past `key_elem`
+fn glob_step<K: PartialEq>(pattern: &[GlobElem<K>], states: &[bool], key_elem: K) -> Vec<bool> {
+    let mut next = vec![false; states.len()];
+    for (i, elem) in pattern.iter().enumerate() {
+        if !states[i] {
+            continue;
+        }
+        match *elem {
+            GlobElem::Exact(ref k) if *k == key_elem => next[i + 1] = true,
+            GlobElem::Exact(_) => {},
+            GlobElem::Any => next[i + 1] = true,
+            GlobElem::AnySeq => next[i] = true,
+        }
+    }
+    glob_closure(pattern, &mut next);
+    next
+}
+
+/// An `AnySeq` can match nothing, so reaching one means reaching the position after it too
+fn glob_closure<K>(pattern: &[GlobElem<K>], states: &mut [bool]) {
+    for (i, elem) in pattern.iter().enumerate() {
+        if states[i] {
+            if let GlobElem::AnySeq = *elem {
+                states[i + 1] = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use Trie;
+    use super::GlobElem::{self, Any, AnySeq, Exact};
+
+    fn pattern(glob: &str) -> Vec<GlobElem<char>> {
+        glob.chars()
+            .map(|c| match c {
+                '?' => Any,
+                '*' => AnySeq,
+                c => Exact(c),
+            })
+            .collect()
+    }
+
+    fn matches(t: &Trie<char, usize>, glob: &str) -> Vec<String> {
+        t.glob_search(&pattern(glob)).into_iter().map(|(k, _)| k.into_iter().collect()).collect()
+    }
+
+    fn words() -> Trie<char, usize> {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["cat", "cot", "cart", "ct", "coat", "dog", ""].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_glob_search() {
+        let t = words();
+
+        assert_eq!(matches(&t, "c?t"), vec!["cat", "cot"]);
+        assert_eq!(matches(&t, "c*"), vec!["cat", "cart", "cot", "coat", "ct"]);
+        assert_eq!(matches(&t, "c*t"), vec!["cat", "cart", "cot", "coat", "ct"]);
+        assert_eq!(matches(&t, "c??t"), vec!["cart", "coat"]);
+        assert_eq!(matches(&t, "dog"), vec!["dog"]);
+        assert_eq!(matches(&t, "do"), Vec::<String>::new());
+        assert_eq!(t.glob_search(&pattern("cat")), vec![(vec!['c', 'a', 't'], &0)]);
+    }
+
+    #[test]
+    fn test_glob_search_any_seq() {
+        let t = words();
+
+        assert_eq!(matches(&t, "").len(), 1);
+        assert_eq!(matches(&t, "*").len(), 7);
+        // several ways to match, but each key is found once
+        assert_eq!(matches(&t, "**a**"), vec!["cat", "cart", "coat"]);
+        assert_eq!(matches(&t, "*t"), vec!["cat", "cart", "cot", "coat", "ct"]);
+    }
+}
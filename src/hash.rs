@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {ErrType, Trie, TrieError};
+
+/// A Trie whose children are kept in a `HashMap`
+///
+/// Each level of a descent is a single hash lookup, instead of [Trie](::Trie)'s linear scan
+/// over its children. That pays off for wide fan-out (e.g. byte keys, with up to 256 children
+/// per node) at the cost of a `HashMap` per node. `insert`, `search` and `get` behave exactly as
+/// they do on `Trie`.
+#[derive(Clone, Debug)]
+pub struct HashTrie<K, D> {
+    children: HashMap<K, HashTrie<K, D>>,
+    data: Option<D>,
+}
+
+impl<K: Hash + Eq + Copy, D> HashTrie<K, D> {
+
+    /// Construct a new, empty HashTrie
+    pub fn new_empty() -> HashTrie<K, D> {
+        HashTrie {
+            children: HashMap::new(),
+            data: None,
+        }
+    }
+
+    /// Insert a new value into the HashTrie
+    ///
+    /// Inserting a key that is already present is illegal.
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        let mut node = self;
+        for key_elem in key {
+            node = node.children.entry(key_elem).or_insert_with(HashTrie::new_empty);
+        }
+        match node.data {
+            None => {
+                node.data = Some(data);
+                Ok(())
+            }
+            Some(_) => {
+                Err(TrieError::KeyAlreadyPresent)
+            }
+        }
+    }
+
+    /// Search for the longest match in the HashTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = self;
+        let mut longest = self.data.as_ref();
+        for key_elem in key {
+            node = match node.children.get(&key_elem) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(ref data) = node.data {
+                longest = Some(data);
+            }
+        }
+        longest
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = self;
+        for key_elem in key {
+            node = node.children.get(&key_elem)?;
+        }
+        node.data.as_ref()
+    }
+}
+
+impl<K: Hash + Eq + Copy, D> Default for HashTrie<K, D> {
+
+    fn default() -> HashTrie<K, D> {
+        HashTrie::new_empty()
+    }
+}
+
+impl<K, D> Drop for HashTrie<K, D> {
+
+    /// Tear the HashTrie down a node at a time, since the default recursive drop would overflow
+    /// the stack on a very long key
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.children.drain().map(|(_, child)| child).collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.children.drain().map(|(_, child)| child));
+        }
+    }
+}
+
+impl<K: Eq + Copy + Hash, D> Trie<K, D> {
+
+    /// Copy every entry into a flat `HashMap` from whole keys to values
+    pub fn to_hashmap(&self) -> HashMap<Vec<K>, D> where D: Clone {
+        self.iter().map(|(key, data)| (key, data.clone())).collect()
+    }
+
+    /// Build a Trie from a flat `HashMap` from whole keys to values
+    pub fn from_hashmap(map: HashMap<Vec<K>, D>) -> Trie<K, D> {
+        map.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use Trie;
+    use super::HashTrie;
+
+    #[test]
+    fn test_hash_trie() {
+        let mut t = HashTrie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 2), Ok(()));
+        assert!(t.insert("ab".chars(), 3).is_err());
+
+        assert_eq!(t.get("abc".chars()), Some(&1));
+        assert_eq!(t.get("ab".chars()), Some(&2));
+        assert_eq!(t.get("a".chars()), None);
+        assert_eq!(t.get("abcd".chars()), None);
+
+        assert_eq!(t.search("abcdef".chars()), Some(&1));
+        assert_eq!(t.search("abx".chars()), Some(&2));
+        assert_eq!(t.search("a".chars()), None);
+    }
+
+    #[test]
+    fn test_hash_trie_matches_trie() {
+        let keys: Vec<Vec<u8>> = vec![
+            vec![], vec![0], vec![0, 255], vec![0, 255, 7], vec![1, 2, 3], vec![1, 2, 4, 5],
+            vec![200, 100],
+        ];
+        let mut t = Trie::new_empty();
+        let mut h = HashTrie::new_empty();
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(t.insert(key.iter().cloned(), i), Ok(()));
+            assert_eq!(h.insert(key.iter().cloned(), i), Ok(()));
+        }
+
+        let queries: Vec<Vec<u8>> = vec![
+            vec![], vec![0], vec![0, 254], vec![0, 255, 7, 7], vec![1, 2], vec![1, 2, 4],
+            vec![1, 2, 4, 5, 6], vec![200], vec![9],
+        ];
+        for query in queries.iter() {
+            assert_eq!(h.get(query.iter().cloned()), t.get(query.iter().cloned()));
+            assert_eq!(h.search(query.iter().cloned()), t.search(query.iter().cloned()));
+        }
+    }
+
+    #[test]
+    fn test_hashmap_round_trip() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["", "a", "ab", "abc", "b", "xyz"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let map = t.to_hashmap();
+        assert_eq!(map.len(), 6);
+        assert_eq!(map.get(&"ab".chars().collect::<Vec<_>>()), Some(&2));
+        assert_eq!(Trie::from_hashmap(map), t);
+
+        let empty: Trie<char, usize> = Trie::from_hashmap(HashMap::new());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_very_long_key() {
+        let len = 100_000;
+        let mut h = HashTrie::new_empty();
+        assert_eq!(h.insert((0..len).map(|i| i % 7), "long"), Ok(()));
+        assert_eq!(h.insert((0..10).map(|i| i % 7), "short"), Ok(()));
+        assert_eq!(h.get((0..len).map(|i| i % 7)), Some(&"long"));
+        assert_eq!(h.search((0..len - 1).map(|i| i % 7)), Some(&"short"));
+        drop(h);
+    }
+}
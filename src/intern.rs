@@ -0,0 +1,163 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {ErrType, Trie};
+
+/// A Trie whose key elements are stored once each, for elements too big to copy into every
+/// node
+///
+/// A [Trie] needs `Copy` key elements, which rules out the likes of `String` path segments.
+/// Here each distinct element is interned the first time it's inserted, and the nodes hold its
+/// 4-byte id instead, so a segment repeated across thousands of keys is kept in memory once.
+/// Lookups map each query element to its id first; an element that's never been inserted
+/// can't be part of any stored key.
+///
+/// Interning is opt-in: it's this wrapper rather than something [Trie] does behind its own
+/// API, since a `Copy` element owns no heap memory for interning to save. The methods it has
+/// follow the Trie's, taking owned elements to insert and borrowed ones to look up.
+#[derive(Clone, Debug)]
+pub struct InternedTrie<E: Hash + Eq, D> {
+    trie: Trie<u32, D>,
+    ids: HashMap<E, u32>,
+    elements: Vec<E>,
+}
+
+impl<E: Hash + Eq + Clone, D> InternedTrie<E, D> {
+
+    /// Construct a new, empty InternedTrie
+    pub fn new_empty() -> InternedTrie<E, D> {
+        InternedTrie {
+            trie: Trie::new_empty(),
+            ids: HashMap::new(),
+            elements: vec![],
+        }
+    }
+
+    /// Insert a new value into the InternedTrie, interning any new key elements
+    ///
+    /// Inserting a key that is already present is illegal.
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` distinct elements are interned.
+    pub fn insert<F: IntoIterator<Item=E>>(&mut self, key: F, data: D) -> ErrType {
+        let ids: Vec<u32> = key.into_iter().map(|elem| self.intern(elem)).collect();
+        self.trie.insert(ids, data)
+    }
+
+    /// Search for the longest match in the InternedTrie
+    pub fn search<'q, Q, F>(&self, key: F) -> Option<&D>
+            where Q: Hash + Eq + ?Sized + 'q, E: Borrow<Q>, F: IntoIterator<Item=&'q Q> {
+        // no stored key goes past an element that's never been interned
+        self.trie.search_iter(key.into_iter().map_while(|elem| self.ids.get(elem).cloned()))
+    }
+
+    /// Look up the value stored at exactly this key
+    ///
+    /// Query elements are borrowed, as with `HashMap::get`, so a key of `String`s can be looked
+    /// up with `&str`s.
+    pub fn get<'q, Q, F>(&self, key: F) -> Option<&D>
+            where Q: Hash + Eq + ?Sized + 'q, E: Borrow<Q>, F: IntoIterator<Item=&'q Q> {
+        let ids: Option<Vec<u32>> = key.into_iter().map(|elem| self.ids.get(elem).cloned()).collect();
+        self.trie.get(ids?)
+    }
+
+    /// Iterate over every key and value, in order of the elements' ids (i.e. first insertion)
+    pub fn iter(&self) -> impl Iterator<Item=(Vec<&E>, &D)> {
+        self.trie.iter().map(move |(ids, data)| {
+            (ids.into_iter().map(|id| &self.elements[id as usize]).collect(), data)
+        })
+    }
+
+    /// Count the values stored in the InternedTrie
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    /// Check whether the InternedTrie holds no values at all
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// Count the distinct key elements interned so far
+    pub fn interned_len(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn intern(&mut self, elem: E) -> u32 {
+        if let Some(&id) = self.ids.get(&elem) {
+            return id;
+        }
+        assert!(self.elements.len() < u32::MAX as usize, "too many distinct key elements");
+        let id = self.elements.len() as u32;
+        self.elements.push(elem.clone());
+        self.ids.insert(elem, id);
+        id
+    }
+}
+
+impl<E: Hash + Eq + Clone, D> Default for InternedTrie<E, D> {
+
+    fn default() -> InternedTrie<E, D> {
+        InternedTrie::new_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use TrieError;
+    use super::InternedTrie;
+
+    fn segments(path: &str) -> Vec<String> {
+        path.split('/').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_interned_trie() {
+        let mut t = InternedTrie::new_empty();
+        assert_eq!(t.insert(segments("/usr/local/bin"), 1), Ok(()));
+        assert_eq!(t.insert(segments("/usr/bin"), 2), Ok(()));
+        assert_eq!(t.insert(segments("/usr"), 3), Ok(()));
+        assert_eq!(t.insert(segments("/usr/bin"), 4), Err(TrieError::KeyAlreadyPresent));
+
+        // lookups can borrow their elements
+        assert_eq!(t.get(vec!["usr", "local", "bin"]), Some(&1));
+        assert_eq!(t.get(&segments("/usr/bin")), Some(&2));
+        assert_eq!(t.get(vec!["usr", "local"]), None);
+        assert_eq!(t.get(vec!["usr", "never-seen"]), None);
+        assert_eq!(t.search(vec!["usr", "local", "bin", "ls"]), Some(&1));
+        assert_eq!(t.search(vec!["usr", "share"]), Some(&3));
+        assert_eq!(t.search(vec!["etc"]), None);
+
+        let entries: Vec<(Vec<&String>, &usize)> = t.iter().collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].0, vec!["usr"]);
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.interned_len(), 3);
+    }
+
+    #[test]
+    fn test_interning_saves_memory() {
+        let mut t = InternedTrie::new_empty();
+        let mut stored_bytes = 0;
+        let mut nodes = 0;
+        for user in 0..100 {
+            for dir in ["documents", "downloads", "pictures", "projects"].iter() {
+                let path = format!("/home/user{}/{}/archive", user, dir);
+                let key = segments(&path);
+                stored_bytes += key.iter().map(|s| mem::size_of::<String>() + s.len()).sum::<usize>();
+                nodes += key.len();
+                assert_eq!(t.insert(key, ()), Ok(()));
+            }
+        }
+
+        // "home", the four directories and "archive" are shared by every user
+        assert_eq!(t.interned_len(), 100 + 6);
+        let interned_bytes: usize = t.elements.iter().map(|s| mem::size_of::<String>() + s.len()).sum();
+        let id_bytes = nodes * mem::size_of::<u32>();
+        assert!(interned_bytes + id_bytes < stored_bytes / 4);
+    }
+}
@@ -0,0 +1,394 @@
+use alloc::collections::VecDeque;
+use alloc::vec::{self, Vec};
+use core::marker::PhantomData;
+use core::mem;
+use core::slice;
+
+use Trie;
+
+/// An iterator over the entries of a [Trie], in depth-first order
+///
+/// Created by [Trie::iter]. Keys aren't stored whole, so each one is rebuilt from the path
+/// walked down to its node.
+pub struct Iter<'a, K: 'a, D: 'a> {
+    start: Option<&'a Trie<K, D>>,
+    stack: Vec<slice::Iter<'a, Trie<K, D>>>,
+    path: Vec<K>,
+}
+
+impl<'a, K: Copy, D> Iter<'a, K, D> {
+
+    /// Iterate over `start` and everything beneath it, with every key beginning with `path`
+    pub(crate) fn new(start: Option<&'a Trie<K, D>>, path: Vec<K>) -> Iter<'a, K, D> {
+        Iter {
+            start,
+            stack: vec![],
+            path,
+        }
+    }
+}
+
+impl<'a, K: Copy, D> Iterator for Iter<'a, K, D> {
+    type Item = (Vec<K>, &'a D);
+
+    fn next(&mut self) -> Option<(Vec<K>, &'a D)> {
+        if let Some(start) = self.start.take() {
+            self.stack.push(start.children.iter());
+            if let Some(ref data) = start.data {
+                return Some((self.path.clone(), data));
+            }
+        }
+        loop {
+            let next_child = self.stack.last_mut()?.next();
+            match next_child {
+                Some(child) => {
+                    self.path.extend(child.key);
+                    self.stack.push(child.children.iter());
+                    if let Some(ref data) = child.data {
+                        return Some((self.path.clone(), data));
+                    }
+                },
+                None => {
+                    // done with this node; its key element is at the end of the path, unless
+                    // we're back at the start (whose path belongs to the caller)
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the entries of a [Trie], in breadth-first order
+///
+/// Created by [Trie::iter_bfs]. Shorter keys come out before longer ones; keys of the same
+/// length come out in the order their nodes were added. Every queued node carries its own copy
+/// of its key.
+pub struct IterBfs<'a, K: 'a, D: 'a> {
+    queue: VecDeque<(&'a Trie<K, D>, Vec<K>)>,
+}
+
+impl<'a, K: Copy, D> IterBfs<'a, K, D> {
+
+    pub(crate) fn new(start: &'a Trie<K, D>) -> IterBfs<'a, K, D> {
+        let mut queue = VecDeque::new();
+        queue.push_back((start, vec![]));
+        IterBfs { queue }
+    }
+}
+
+impl<'a, K: Copy, D> Iterator for IterBfs<'a, K, D> {
+    type Item = (Vec<K>, &'a D);
+
+    fn next(&mut self) -> Option<(Vec<K>, &'a D)> {
+        while let Some((node, path)) = self.queue.pop_front() {
+            for child in node.children.iter() {
+                let mut child_path = path.clone();
+                child_path.extend(child.key);
+                self.queue.push_back((child, child_path));
+            }
+            if let Some(ref data) = node.data {
+                return Some((path, data));
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the keys of a [Trie], in depth-first order
+///
+/// Created by [Trie::keys].
+pub struct Keys<'a, K: 'a, D: 'a> {
+    inner: Iter<'a, K, D>,
+}
+
+impl<'a, K: Copy, D> Keys<'a, K, D> {
+
+    pub(crate) fn new(inner: Iter<'a, K, D>) -> Keys<'a, K, D> {
+        Keys { inner }
+    }
+}
+
+impl<'a, K: Copy, D> Iterator for Keys<'a, K, D> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Vec<K>> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+/// An iterator over the values of a [Trie], in depth-first order
+///
+/// Created by [Trie::values]. Keys are never reconstructed.
+pub struct Values<'a, K: 'a, D: 'a> {
+    start: Option<&'a Trie<K, D>>,
+    stack: Vec<slice::Iter<'a, Trie<K, D>>>,
+}
+
+impl<'a, K, D> Values<'a, K, D> {
+
+    pub(crate) fn new(start: Option<&'a Trie<K, D>>) -> Values<'a, K, D> {
+        Values {
+            start,
+            stack: vec![],
+        }
+    }
+}
+
+impl<'a, K, D> Iterator for Values<'a, K, D> {
+    type Item = &'a D;
+
+    fn next(&mut self) -> Option<&'a D> {
+        if let Some(start) = self.start.take() {
+            self.stack.push(start.children.iter());
+            if let Some(ref data) = start.data {
+                return Some(data);
+            }
+        }
+        loop {
+            let next_child = self.stack.last_mut()?.next();
+            match next_child {
+                Some(child) => {
+                    self.stack.push(child.children.iter());
+                    if let Some(ref data) = child.data {
+                        return Some(data);
+                    }
+                },
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// A mutable iterator over the values of a [Trie], in depth-first order
+///
+/// Created by [Trie::values_mut].
+pub struct ValuesMut<'a, K: 'a, D: 'a> {
+    start: Option<&'a mut Trie<K, D>>,
+    stack: Vec<slice::IterMut<'a, Trie<K, D>>>,
+}
+
+impl<'a, K, D> ValuesMut<'a, K, D> {
+
+    pub(crate) fn new(start: Option<&'a mut Trie<K, D>>) -> ValuesMut<'a, K, D> {
+        ValuesMut {
+            start,
+            stack: vec![],
+        }
+    }
+}
+
+impl<'a, K, D> Iterator for ValuesMut<'a, K, D> {
+    type Item = &'a mut D;
+
+    fn next(&mut self) -> Option<&'a mut D> {
+        if let Some(start) = self.start.take() {
+            // borrow the two fields separately so we can hand out the data while still
+            // walking the children later
+            let Trie { ref mut children, ref mut data, .. } = *start;
+            self.stack.push(children.iter_mut());
+            if let Some(data) = data.as_mut() {
+                return Some(data);
+            }
+        }
+        loop {
+            let next_child = self.stack.last_mut()?.next();
+            match next_child {
+                Some(child) => {
+                    let Trie { ref mut children, ref mut data, .. } = *child;
+                    self.stack.push(children.iter_mut());
+                    if let Some(data) = data.as_mut() {
+                        return Some(data);
+                    }
+                },
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An owning iterator over the entries of a [Trie], in depth-first order
+///
+/// Created by consuming a Trie with `into_iter`. Values are moved out, so `D` doesn't need to
+/// be `Clone`.
+pub struct IntoIter<K, D> {
+    start: Option<Trie<K, D>>,
+    stack: Vec<vec::IntoIter<Trie<K, D>>>,
+    path: Vec<K>,
+}
+
+impl<K: Copy, D> IntoIter<K, D> {
+
+    pub(crate) fn new(start: Option<Trie<K, D>>, path: Vec<K>) -> IntoIter<K, D> {
+        IntoIter {
+            start,
+            stack: vec![],
+            path,
+        }
+    }
+}
+
+impl<K: Copy, D> Iterator for IntoIter<K, D> {
+    type Item = (Vec<K>, D);
+
+    fn next(&mut self) -> Option<(Vec<K>, D)> {
+        if let Some(start) = self.start.take() {
+            let mut start = start;
+            self.stack.push(mem::take(&mut start.children).into_iter());
+            if let Some(data) = start.data.take() {
+                return Some((self.path.clone(), data));
+            }
+        }
+        loop {
+            let next_child = self.stack.last_mut()?.next();
+            match next_child {
+                Some(child) => {
+                    let mut child = child;
+                    self.path.extend(child.key);
+                    self.stack.push(mem::take(&mut child.children).into_iter());
+                    if let Some(data) = child.data.take() {
+                        return Some((self.path.clone(), data));
+                    }
+                },
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An owning iterator over the keys of a [Trie], in depth-first order
+///
+/// Created by [Trie::into_keys].
+pub struct IntoKeys<K, D> {
+    inner: IntoIter<K, D>,
+}
+
+impl<K: Copy, D> IntoKeys<K, D> {
+
+    pub(crate) fn new(inner: IntoIter<K, D>) -> IntoKeys<K, D> {
+        IntoKeys { inner }
+    }
+}
+
+impl<K: Copy, D> Iterator for IntoKeys<K, D> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Vec<K>> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+/// An owning iterator over the values of a [Trie], in depth-first order
+///
+/// Created by [Trie::into_values]. Keys are never reconstructed.
+pub struct IntoValues<K, D> {
+    start: Option<Trie<K, D>>,
+    stack: Vec<vec::IntoIter<Trie<K, D>>>,
+}
+
+impl<K, D> IntoValues<K, D> {
+
+    pub(crate) fn new(start: Option<Trie<K, D>>) -> IntoValues<K, D> {
+        IntoValues {
+            start,
+            stack: vec![],
+        }
+    }
+}
+
+impl<K, D> Iterator for IntoValues<K, D> {
+    type Item = D;
+
+    fn next(&mut self) -> Option<D> {
+        if let Some(start) = self.start.take() {
+            let mut start = start;
+            self.stack.push(mem::take(&mut start.children).into_iter());
+            if start.data.is_some() {
+                return start.data.take();
+            }
+        }
+        loop {
+            let next_child = self.stack.last_mut()?.next();
+            match next_child {
+                Some(child) => {
+                    let mut child = child;
+                    self.stack.push(mem::take(&mut child.children).into_iter());
+                    if child.data.is_some() {
+                        return child.data.take();
+                    }
+                },
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// A draining iterator over the entries of a [Trie], in depth-first order
+///
+/// Created by [Trie::drain]. The Trie is emptied as soon as this is created, so it is empty
+/// afterwards whether or not every entry was consumed.
+pub struct Drain<'a, K: 'a, D: 'a> {
+    inner: IntoIter<K, D>,
+    marker: PhantomData<&'a mut Trie<K, D>>,
+}
+
+impl<'a, K: Copy, D> Drain<'a, K, D> {
+
+    pub(crate) fn new(inner: IntoIter<K, D>) -> Drain<'a, K, D> {
+        Drain {
+            inner,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: Copy, D> Iterator for Drain<'a, K, D> {
+    type Item = (Vec<K>, D);
+
+    fn next(&mut self) -> Option<(Vec<K>, D)> {
+        self.inner.next()
+    }
+}
+
+/// Walks down a [Trie] along a key, yielding the starting node and then each node matched
+///
+/// Stops at the end of the key or at the first element with no matching child.
+pub(crate) struct Path<'a, K: 'a, D: 'a, F> {
+    next: Option<&'a Trie<K, D>>,
+    key_elems: F,
+}
+
+impl<'a, K: PartialEq + Copy, D, F: Iterator<Item=K>> Path<'a, K, D, F> {
+
+    pub(crate) fn new(start: &'a Trie<K, D>, key_elems: F) -> Path<'a, K, D, F> {
+        Path {
+            next: Some(start),
+            key_elems,
+        }
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D, F: Iterator<Item=K>> Iterator for Path<'a, K, D, F> {
+    type Item = &'a Trie<K, D>;
+
+    fn next(&mut self) -> Option<&'a Trie<K, D>> {
+        let node = self.next.take()?;
+        if let Some(key_elem) = self.key_elems.next() {
+            self.next = node.child_position(key_elem).map(|index| &node.children[index]);
+        }
+        Some(node)
+    }
+}
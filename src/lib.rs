@@ -1,6 +1,85 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate core;
+// the tests borrow a few things from std (a hasher, Cell) even without the std feature
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+use alloc::collections::BinaryHeap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::Index;
+
+mod builder;
+#[cfg(feature = "std")]
+mod cache;
+mod cmp;
+mod diff;
+mod dot;
+mod double_array;
+mod entry;
+mod fnv;
+mod frozen;
+mod fuzzy;
+mod glob;
+#[cfg(feature = "std")]
+mod hash;
+#[cfg(feature = "std")]
+mod intern;
+mod iter;
+mod multi;
+#[cfg(feature = "std")]
+mod persist;
+mod prefix;
+mod radix;
+mod rc;
+mod routing;
+mod sorted;
+mod stats;
+mod tst;
+mod visit;
+
+pub use builder::TrieBuilder;
+#[cfg(feature = "std")]
+pub use cache::CachedTrie;
+pub use cmp::{CaseInsensitive, ElementCmp, Exact, TrieWith};
+pub use diff::{TrieDiff, TriePatch};
+pub use double_array::DoubleArrayTrie;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+use fnv::FnvHasher;
+pub use frozen::FrozenTrie;
+pub use glob::GlobElem;
+#[cfg(feature = "std")]
+pub use hash::HashTrie;
+#[cfg(feature = "std")]
+pub use intern::InternedTrie;
+pub use iter::{Drain, IntoIter, IntoKeys, IntoValues, Iter, IterBfs, Keys, Values, ValuesMut};
+use iter::Path;
+pub use multi::MultiTrie;
+pub use prefix::PrefixView;
+pub use radix::RadixTrie;
+pub use rc::RcTrie;
+pub use sorted::SortedTrie;
+pub use stats::TrieStats;
+pub use tst::{TstIter, TstTrie};
+pub use visit::TrieVisitor;
+
 /// Generic Trie implementation
 ///
-/// Doesn't feature any Patricia optimizations (each node has only a single key)
+/// Doesn't feature any Patricia optimizations (each node has only a single key); use
+/// [Trie::compress] to get a [RadixTrie] once it's built.
+///
+/// Each node's children are kept in the order they were first added and found with a linear scan,
+/// so any `PartialEq + Copy` type works as a key element. For `Ord` key elements, [SortedTrie]
+/// keeps them sorted and binary searches them instead.
 ///
 /// # Examples
 ///
@@ -15,25 +94,62 @@
 ///     assert_eq!(value, "foobar");
 /// }
 /// ```
-
-#[derive(Debug)]
 pub struct Trie<K, D> {
     children: Vec<Trie<K, D>>,
     key: Option<K>,
     data: Option<D>,
 }
 
-pub type ErrType = Result<(), &'static str>;
+/// The ways a Trie operation can fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrieError {
+    /// The key being inserted already holds a value
+    KeyAlreadyPresent,
+}
+
+impl fmt::Display for TrieError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrieError::KeyAlreadyPresent => write!(f, "key already present"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for TrieError {}
+
+pub type ErrType = Result<(), TrieError>;
 
 impl<K: PartialEq + Copy, D> Trie<K, D> {
 
     /// Construct a new, empty Trie
     pub fn new_empty() -> Trie<K, D> {
-        Trie {
-            children: vec![],
-            key: None,
-            data: None
-        }
+        Trie::default()
+    }
+
+    /// Construct a new, empty Trie with room for `n` children at the root
+    ///
+    /// Handy when the root's fan-out is known up front (e.g. 256 for byte keys).
+    pub fn with_capacity(n: usize) -> Trie<K, D> {
+        let mut trie = Trie::new_empty();
+        trie.reserve(n);
+        trie
+    }
+
+    /// Start building a Trie which reserves room for `n` children in every node that gets any
+    ///
+    /// Only a performance knob for bulk inserts: with a dense alphabet each node's children are
+    /// allocated once, when the first is added, instead of growing as more arrive. It costs
+    /// memory on nodes which turn out to have fewer children. The hint is held by the
+    /// [TrieBuilder], not the Trie, so inserts after [TrieBuilder::build] don't use it.
+    pub fn with_alphabet_hint(n: usize) -> TrieBuilder<K, D> {
+        TrieBuilder::new(n)
+    }
+
+    /// Reserve room for at least `additional` more children at the root
+    pub fn reserve(&mut self, additional: usize) {
+        self.children.reserve(additional);
     }
 
     /// Insert a new value into the Trie through an iterator.
@@ -43,37 +159,21 @@ impl<K: PartialEq + Copy, D> Trie<K, D> {
     /// key_elems should be an Iterator over whatever the Key Type is (e.g., an iterator of `char`
     ///     if the KeyType is `char`)
     /// data will be Moved into the Trie
-    pub fn insert_iter<F: Iterator<Item=K>>(&mut self, mut key_elems: F, data: D) -> ErrType {
-        let this_key: Option<K> = key_elems.next();
-
-        if let Some(this_key_value) = this_key {
-            for mut child in self.children.iter_mut() {
-                // If the keys match
-                if let Some(child_key_value) = child.key {
-                    if child_key_value == this_key_value {
-                        // insert into the child!
-                        return child.insert(key_elems, data);
-                    }
-                }
+    pub fn insert_iter<F: Iterator<Item=K>>(&mut self, key_elems: F, data: D) -> ErrType {
+        // walk down a level per key element (not recursively, so long keys can't overflow the
+        // stack), making new children where we have to
+        let mut node = self;
+        for key_elem in key_elems {
+            node = node.child_or_insert(key_elem);
+        }
+
+        match node.data {
+            None => {
+                node.data = Some(data);
+                Ok(())
             }
-            // Guess we have to make a new one
-            let mut new_child = Trie {
-                children: vec![],
-                key: Some(this_key_value),
-                data: None,
-            };
-            let res = new_child.insert(key_elems, data);
-            self.children.push(new_child);
-            return res;
-        } else {
-            return match self.data {
-                None => {
-                    self.data = Some(data);
-                    Ok(())
-                }
-                Some(_) => {
-                    Err("key already present!")
-                }
+            Some(_) => {
+                Err(TrieError::KeyAlreadyPresent)
             }
         }
     }
@@ -82,123 +182,2941 @@ impl<K: PartialEq + Copy, D> Trie<K, D> {
     ///
     /// Syntactic sugar for [self.insert_iter]
     pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
-        return self.insert_iter(key.into_iter(), data);
+        self.insert_iter(key.into_iter(), data)
     }
 
-    /// Search for the longest match in the Trie
-    pub fn search_iter<F: Iterator<Item=K>>(&self, mut key_elems: F) -> Option<&D> {
-        let this_key: Option<K> = key_elems.next();
-
-        // Does the key we got out of the iterator even do anything?
-        match this_key {
-            Some(this_key_value) => {
-                // walk through each children looking for one matching the key
-                for child in self.children.iter() {
-                   // if the keys match
-                    if let Some(child_key_value) = child.key {
-                        if child_key_value == this_key_value {
-                            // recurse
-                            return child.search(key_elems)
-                        }
-                    }
-                }
-                // If we didn't find anything recursively, but we have a data,
-                // then *we* must be the longest match!
-                match self.data {
-                    Some(ref data_val) => return Some(&data_val),
-                    None => None
-                }
+    /// Insert every pair from a batch, carrying on past failures
+    ///
+    /// Returns the position in the batch of each pair which couldn't be inserted, with why (a
+    /// key already present, including one earlier in the same batch). Everything else is
+    /// inserted.
+    pub fn insert_many<I: IntoIterator<Item=(Vec<K>, D)>>(&mut self, pairs: I) -> Vec<(usize, TrieError)> {
+        pairs.into_iter()
+            .enumerate()
+            .filter_map(|(i, (key, data))| self.insert(key, data).err().map(|err| (i, err)))
+            .collect()
+    }
+
+    /// Insert a value, replacing and returning any value already stored at the key
+    ///
+    /// This is the `HashMap::insert` contract; [self.insert] errors on an existing key instead.
+    pub fn insert_or_replace<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> Option<D> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(data)),
+            Entry::Vacant(entry) => {
+                entry.insert(data);
+                None
             },
-            None => match self.data {
-                Some(ref data_val) => return Some(&data_val),
-                None => return None,
-            }
         }
     }
 
+    /// Search for the longest match in the Trie
+    pub fn search_iter<F: Iterator<Item=K>>(&self, key_elems: F) -> Option<&D> {
+        // the deepest node with data along the way is the longest match
+        self.path(key_elems).filter_map(|node| node.data.as_ref()).last()
+    }
+
     /// Search for a value in the Trie given an interator
     ///
     /// Syntactic sugar for [self.search_iter]
     pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
-        return self.search_iter(key.into_iter());
+        self.search_iter(key.into_iter())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Trie;
+    /// Search for the longest match in the Trie, returning a mutable reference to its value
+    ///
+    /// The mutable counterpart of [self.search], e.g. for bumping a counter on the matched route.
+    pub fn search_mut<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&mut D> {
+        // a mutable borrow can't be held at each match while descending past it, so record the
+        // path of child indices first and then walk back down to the deepest match
+        let mut indices = Vec::new();
+        let mut matched = self.data.as_ref().map(|_| 0);
+        let mut node = &*self;
+        for key_elem in key {
+            match node.child_position(key_elem) {
+                Some(index) => {
+                    indices.push(index);
+                    node = &node.children[index];
+                    if node.data.is_some() {
+                        matched = Some(indices.len());
+                    }
+                },
+                None => break,
+            }
+        }
+        let mut node = self;
+        for &index in &indices[..matched?] {
+            node = &mut node.children[index];
+        }
+        node.data.as_mut()
+    }
 
-    #[test]
-    fn create_trie() {
-        let mut t = Trie::new_empty();
+    /// Search for the longest match in the Trie, also returning how many key elements it
+    /// matched
+    ///
+    /// A length shorter than the key means only a stored prefix of it matched.
+    pub fn search_with_len<F: IntoIterator<Item=K>>(&self, key: F) -> Option<(usize, &D)> {
+        self.path(key.into_iter())
+            .enumerate()
+            .filter_map(|(len, node)| node.data.as_ref().map(|data| (len, data)))
+            .last()
+    }
 
-        assert_eq!(t.insert("abc".chars(), "foobar".to_string()), Ok(()));
+    /// The value (or lack of one) at each node along the key, starting from the root
+    ///
+    /// The descent stops at the first key element with no matching child, so the result has
+    /// one entry per matched element plus one for the root, and its last value is what
+    /// [self.get] would return for the matched part.
+    pub fn path_values<F: IntoIterator<Item=K>>(&self, key: F) -> Vec<Option<&D>> {
+        self.path(key.into_iter()).map(|node| node.data.as_ref()).collect()
+    }
 
-        assert_eq!(t.search("ab".chars()), None);
+    /// Search for the shortest stored prefix of the key
+    ///
+    /// The opposite of [self.search]: the first value met on the way down wins, so the
+    /// broadest match is returned.
+    pub fn shortest_match<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        self.path(key.into_iter()).filter_map(|node| node.data.as_ref()).next()
+    }
 
-        let res = t.search("abc".chars());
-        assert!(res != None);
-        if let Some(value) = res {
-            assert_eq!(value, "foobar")
+    /// How many leading elements of the key have a matching path in the Trie
+    ///
+    /// The matched part needn't be (or lead to) a stored key of its own length; it's just how
+    /// far the key descends before diverging.
+    pub fn common_prefix_len<F: IntoIterator<Item=K>>(&self, key: F) -> usize {
+        // the path starts at the root, which matches nothing
+        self.path(key.into_iter()).count() - 1
+    }
+
+    /// Collect the value of every stored prefix of the key, shortest first
+    ///
+    /// The last one (if any) is what [self.search] would return.
+    pub fn search_all<F: IntoIterator<Item=K>>(&self, key: F) -> Vec<&D> {
+        self.path(key.into_iter()).filter_map(|node| node.data.as_ref()).collect()
+    }
+
+    /// Collect every stored prefix of the key with its value, shortest first
+    ///
+    /// Like [self.search_all], but each key is returned alongside its value.
+    pub fn prefixes_of<F: IntoIterator<Item=K>>(&self, key: F) -> Vec<(Vec<K>, &D)> {
+        let mut prefix = vec![];
+        let mut found = vec![];
+        for node in self.path(key.into_iter()) {
+            prefix.extend(node.key);
+            if let Some(ref data) = node.data {
+                found.push((prefix.clone(), data));
+            }
         }
+        found
     }
 
-    #[test]
-    fn test_longest_match() {
-        let mut t = Trie::new_empty();
-        assert_eq!(t.insert("abc".chars(), "object 1".to_string()), Ok(()));
-        assert_eq!(t.insert("ab".chars(), "object 2".to_string()), Ok(()));
+    /// Look up the value stored at exactly this key
+    ///
+    /// Unlike [self.search], a stored prefix of the key is not a match.
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        self.find_node(key.into_iter()).and_then(|node| node.data.as_ref())
+    }
 
-        let res1 = t.search("abc".chars());
+    /// Look up a mutable reference to the value stored at exactly this key
+    pub fn get_mut<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&mut D> {
+        self.find_node_mut(key.into_iter()).and_then(|node| node.data.as_mut())
+    }
 
-        assert!(res1 != None);
-        if let Some(value) = res1 {
-            assert_eq!(value, "object 1");
+    /// Check whether a value is stored at exactly this key
+    pub fn contains_key<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        self.find_node(key.into_iter()).is_some_and(|node| node.data.is_some())
+    }
+
+    /// Get the entry for a key, for in-place lookup-or-insert
+    ///
+    /// Works like `HashMap::entry`: no nodes are created unless a value is inserted into a
+    /// vacant entry.
+    pub fn entry<F: IntoIterator<Item=K>>(&mut self, key: F) -> Entry<'_, K, D> {
+        let key: Vec<K> = key.into_iter().collect();
+        // the index of each child along as much of the key as the Trie already holds
+        let mut indices = vec![];
+        let mut node = &*self;
+        for &key_elem in &key {
+            match node.child_position(key_elem) {
+                Some(index) => {
+                    indices.push(index);
+                    node = &node.children[index];
+                },
+                None => break,
+            }
         }
+        if indices.len() == key.len() && node.data.is_some() {
+            Entry::occupied(self, key, indices)
+        } else {
+            Entry::vacant(self, key, indices)
+        }
+    }
 
-        let res2 = t.search("abcdef".chars());
+    /// Get a mutable reference to the value stored at exactly this key, first storing the
+    /// result of `f` there if there's none
+    ///
+    /// Shorthand for `self.entry(key).or_insert_with(f)`.
+    pub fn get_or_insert_with<F: IntoIterator<Item=K>, G: FnOnce() -> D>(&mut self, key: F, f: G)
+                                                                         -> &mut D {
+        self.entry(key).or_insert_with(f)
+    }
 
-        assert!(res2 != None);
-        if let Some(value) = res2 {
-            assert_eq!(value, "object 1");
+    /// Apply `f` to the value stored at exactly this key, or store `default` there if there's
+    /// none
+    ///
+    /// `f` isn't called on a freshly inserted default.
+    pub fn update<F: IntoIterator<Item=K>, G: FnOnce(&mut D)>(&mut self, key: F, default: D, f: G) {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => f(entry.get_mut()),
+            Entry::Vacant(entry) => {
+                entry.insert(default);
+            },
         }
+    }
 
-        let res3 = t.search("ab".chars());
+    /// Remove a key from the Trie through an iterator, returning its value
+    ///
+    /// Only an exact match is removed. Any nodes left holding neither data nor children are
+    /// pruned, so the Trie doesn't only grow.
+    pub fn remove_iter<F: Iterator<Item=K>>(&mut self, key_elems: F) -> Option<D> {
+        let indices = self.child_path(key_elems)?;
+        self.remove_path(&indices)
+    }
 
-        assert!(res3 != None);
-        if let Some(value) = res3 {
-            assert_eq!(value, "object 2");
+    /// Remove a key from the Trie, returning its value
+    ///
+    /// Syntactic sugar for [self.remove_iter]
+    pub fn remove<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<D> {
+        self.remove_iter(key.into_iter())
+    }
+
+    /// Count the values stored in the Trie
+    ///
+    /// Interior nodes which only lead to longer keys are not counted.
+    pub fn len(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.data.is_some() {
+                count += 1;
+            }
+            stack.extend(&node.children);
         }
+        count
     }
 
-    #[test]
-    fn test_ints_and_vectors() {
-        let mut t: Trie<i32, i32> = Trie::new_empty();
+    /// Check whether the Trie holds no values at all
+    ///
+    /// Stops at the first value found rather than counting them all.
+    pub fn is_empty(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.data.is_some() {
+                return false;
+            }
+            stack.extend(&node.children);
+        }
+        true
+    }
+
+    /// Remove every value from the Trie
+    ///
+    /// The root keeps its allocated capacity for children, so a cleared Trie can be refilled
+    /// without growing from scratch.
+    pub fn clear(&mut self) {
+        self.children.clear();
+        self.data = None;
+    }
 
+    /// Iterate over every key and value in the Trie, depth-first
+    pub fn iter(&self) -> Iter<'_, K, D> {
+        Iter::new(Some(self), vec![])
+    }
 
-        assert_eq!(t.insert(vec![1, 2], 10), Ok(()));
-        assert_eq!(t.insert(vec![1, 2, 3], 20), Ok(()));
+    /// Iterate over every key and value in the Trie, breadth-first
+    ///
+    /// Entries come out shortest key first, which suits shortest-first completion or exploring
+    /// only the top few levels.
+    pub fn iter_bfs(&self) -> IterBfs<'_, K, D> {
+        IterBfs::new(self)
+    }
 
-        assert_eq!(t.search(vec![1]), None);
+    /// Iterate over every key in the Trie, depth-first
+    pub fn keys(&self) -> Keys<'_, K, D> {
+        Keys::new(self.iter())
+    }
 
-        if let Some(value) = t.search(vec![1, 2, 3]) {
-            assert_eq!(value, &20);
-        } else {
-            assert!(false);
+    /// Iterate over every value in the Trie, depth-first
+    pub fn values(&self) -> Values<'_, K, D> {
+        Values::new(Some(self))
+    }
+
+    /// Iterate mutably over every value in the Trie, depth-first
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, D> {
+        ValuesMut::new(Some(self))
+    }
+
+    /// Compress the Trie into a [RadixTrie]
+    ///
+    /// Chains of single-child nodes holding no value become a single node, which saves memory
+    /// and traversal steps for long keys with little branching.
+    pub fn compress(self) -> RadixTrie<K, D> {
+        RadixTrie::from_trie(self)
+    }
+
+    /// Fold every entry of `other` into this Trie
+    ///
+    /// Where both hold a value for the same key, `on_conflict(existing, incoming)` decides which
+    /// value survives. Subtrees only `other` has are moved over whole.
+    pub fn merge<G: Fn(D, D) -> D>(&mut self, other: Trie<K, D>, on_conflict: G) {
+        self.merge_node(other, &on_conflict);
+    }
+
+    /// Move every entry of `other` into this Trie, for when no key is in both
+    ///
+    /// A fast path for [self.merge]: subtrees only `other` has are moved over whole, and only
+    /// shared prefixes are walked.
+    ///
+    /// # Panics
+    ///
+    /// If both Tries hold a value for the same key.
+    pub fn append(&mut self, other: Trie<K, D>) {
+        self.merge_node(other, &|_, _| panic!("Trie::append found a key present in both Tries"));
+    }
+
+    /// The largest number of edges from the root down to any node; 0 for an empty Trie
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            stack.extend(node.children.iter().map(|child| (child, depth + 1)));
         }
+        height
+    }
 
-        if let Some(value) = t.search(vec![1, 2, 4]) {
-            assert_eq!(value, &10);
-        } else {
-            assert!(false);
+    /// Count the nodes in the Trie, interior ones included but not the root
+    ///
+    /// Each node holds one key element, so this is how many elements the Trie stores; for an
+    /// empty Trie it's 0. Comparing it with [self.len] shows how much [self.compress] could
+    /// save.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack: Vec<&Trie<K, D>> = self.children.iter().collect();
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(&node.children);
         }
+        count
     }
 
-    #[test]
-    fn test_double_insert_fails() {
-        let mut t = Trie::new_empty();
+    /// Count the nodes with no children, not including the root
+    ///
+    /// Every leaf holds a value, but values can also sit on interior nodes (a key which is a
+    /// prefix of another), so this is at most [self.len]; the difference is how many stored
+    /// keys have longer completions.
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack: Vec<&Trie<K, D>> = self.children.iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.children.is_empty() {
+                leaves += 1;
+            }
+            stack.extend(&node.children);
+        }
+        leaves
+    }
 
-        assert!(t.insert("ab".chars(), 1).is_ok());
-        assert!(t.insert("ab".chars(), 1).is_err());
+    /// Iterate over every entry whose key starts with `prefix`, depth-first
+    ///
+    /// Yielded keys include the prefix, and a value stored at the prefix itself comes first. If
+    /// nothing is stored under the prefix the iterator is empty.
+    pub fn iter_prefix<F: IntoIterator<Item=K>>(&self, prefix: F) -> Iter<'_, K, D> {
+        let prefix: Vec<K> = prefix.into_iter().collect();
+        match self.find_node(prefix.iter().cloned()) {
+            Some(node) => Iter::new(Some(node), prefix),
+            None => Iter::new(None, vec![]),
+        }
+    }
+
+    /// Up to `limit` complete keys starting with `prefix`, depth-first
+    pub fn autocomplete<F: IntoIterator<Item=K>>(&self, prefix: F, limit: usize) -> Vec<Vec<K>> {
+        self.iter_prefix(prefix).map(|(key, _)| key).take(limit).collect()
+    }
+
+    /// The longest key sequence which every stored key starts with
+    ///
+    /// Empty when the Trie is empty or the keys diverge straight away.
+    pub fn longest_common_prefix(&self) -> Vec<K> {
+        let mut prefix = vec![];
+        let mut node = self;
+        // a value on the way down means a key ends there, which ends the common prefix too
+        while node.data.is_none() && node.children.len() == 1 {
+            node = &node.children[0];
+            prefix.extend(node.key);
+        }
+        prefix
+    }
+
+    /// Move every entry out of the Trie, depth-first, leaving it empty
+    pub fn drain(&mut self) -> Drain<'_, K, D> {
+        Drain::new(self.take_all().into_iter())
+    }
+
+    /// Keep only the entries for which `pred(key, value)` returns true
+    ///
+    /// Nodes which no longer lead to any value are pruned; shared prefixes of retained keys
+    /// are kept.
+    pub fn retain<P: FnMut(&[K], &D) -> bool>(&mut self, mut pred: P) {
+        self.prune_with(|path, node| {
+            if node.data.as_ref().is_some_and(|data| !pred(path, data)) {
+                node.data = None;
+            }
+            true
+        });
+    }
+
+    /// Keep only the entries whose key starts with at least one of `prefixes`
+    ///
+    /// The Trie is walked once, carrying along the prefixes still consistent with the path so
+    /// far: a subtree is kept whole as soon as one of them is used up, and dropped whole as soon
+    /// as none are left.
+    pub fn retain_prefixes(&mut self, prefixes: &[Vec<K>]) {
+        let candidates: Vec<&[K]> = prefixes.iter().map(|prefix| &prefix[..]).collect();
+        self.retain_prefixes_node(&candidates, 0);
+    }
+
+    /// Count the stored keys which start with `prefix`
+    ///
+    /// Cheaper than collecting [self.iter_prefix] when only the number is needed.
+    pub fn count_prefix<F: IntoIterator<Item=K>>(&self, prefix: F) -> usize {
+        self.find_node(prefix.into_iter()).map_or(0, |node| node.len())
+    }
+
+    /// Check whether any stored key starts with `prefix`
+    ///
+    /// Stops at the prefix's node rather than counting what's under it, so it's cheaper than
+    /// [self.count_prefix]. The prefix itself needn't be a stored key.
+    pub fn contains_prefix<F: IntoIterator<Item=K>>(&self, prefix: F) -> bool {
+        // removal prunes nodes which lead nowhere, so only an empty root can be dead
+        self.find_node(prefix.into_iter()).is_some_and(|node| !node.is_dead())
+    }
+
+    /// How many children the node at `prefix` has, i.e. how many distinct elements can follow it
+    ///
+    /// 0 if the prefix isn't in the Trie. The same as `self.next_elements(prefix).len()`
+    /// without collecting the elements.
+    pub fn fanout<F: IntoIterator<Item=K>>(&self, prefix: F) -> usize {
+        self.find_node(prefix.into_iter()).map_or(0, |node| node.children.len())
+    }
+
+    /// Check whether some stored key is strictly longer than `key` and starts with it
+    ///
+    /// Unlike [self.contains_prefix], a key that's stored but not extended by anything doesn't
+    /// count: with only "ab" stored, "a" is a proper prefix and "ab" isn't.
+    pub fn is_proper_prefix<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        // every child leads to a value, so any child at all means a longer key
+        self.find_node(key.into_iter()).is_some_and(|node| !node.children.is_empty())
+    }
+
+    /// The key elements which can follow `prefix`, in the order they were first added
+    ///
+    /// Empty if the prefix isn't in the Trie or nothing extends it.
+    pub fn next_elements<F: IntoIterator<Item=K>>(&self, prefix: F) -> Vec<K> {
+        match self.find_node(prefix.into_iter()) {
+            Some(node) => node.children.iter().filter_map(|child| child.key).collect(),
+            None => vec![],
+        }
+    }
+
+    /// Remove every key starting with `prefix`, returning how many were removed
+    ///
+    /// The whole subtree under the prefix is detached at once, and any ancestors left leading
+    /// nowhere are pruned. An empty prefix clears the Trie.
+    pub fn remove_prefix<F: IntoIterator<Item=K>>(&mut self, prefix: F) -> usize {
+        let mut prefix = prefix.into_iter();
+        match prefix.next() {
+            Some(first) => self.detach_child(first, prefix).map_or(0, |subtree| subtree.len()),
+            None => {
+                let removed = self.len();
+                self.clear();
+                removed
+            },
+        }
+    }
+
+    /// Remove every entry under `prefix` and return them in a new Trie
+    ///
+    /// Unlike [Trie::take_subtree], keys in the returned Trie keep the full prefix, so the two
+    /// halves can be put back together with [Trie::merge]. If nothing is stored under the
+    /// prefix, the returned Trie is empty.
+    pub fn split_off<F: IntoIterator<Item=K>>(&mut self, prefix: F) -> Trie<K, D> {
+        let prefix: Vec<K> = prefix.into_iter().collect();
+        let init = match prefix.split_last() {
+            Some((_, init)) => init,
+            None => return self.take_all(),
+        };
+        let mut split = Trie::new_empty();
+        if let Some(mut subtree) = self.detach_child(prefix[0], prefix[1..].iter().cloned()) {
+            for &key_elem in init.iter().rev() {
+                let mut parent = Trie::new_node(key_elem);
+                parent.children.push(subtree);
+                subtree = parent;
+            }
+            split.children.push(subtree);
+        }
+        split
+    }
+
+    /// Copy the subtree under `prefix` into a Trie of its own, leaving this one untouched
+    ///
+    /// Keys in the copy are relative to the prefix, as with [Trie::take_subtree]. Returns
+    /// `None` if there's no such prefix path.
+    pub fn clone_subtree<F: IntoIterator<Item=K>>(&self, prefix: F) -> Option<Trie<K, D>>
+            where D: Clone {
+        self.find_node(prefix.into_iter()).map(|node| {
+            let mut subtree = node.clone();
+            subtree.key = None;
+            subtree
+        })
+    }
+
+    /// Remove the subtree under `prefix` and return it as a Trie of its own
+    ///
+    /// Keys in the returned Trie are relative to the prefix (a value stored at the prefix
+    /// itself ends up under the empty key). Returns `None` if there's no such prefix path.
+    pub fn take_subtree<F: IntoIterator<Item=K>>(&mut self, prefix: F) -> Option<Trie<K, D>> {
+        let mut prefix = prefix.into_iter();
+        match prefix.next() {
+            Some(first) => self.detach_child(first, prefix).map(|mut subtree| {
+                subtree.key = None;
+                subtree
+            }),
+            None => Some(self.take_all()),
+        }
+    }
+
+    /// Remove every node which doesn't lead to a value, bottom-up
+    ///
+    /// Removal through the Trie's own methods already prunes as it goes, so this only finds
+    /// work after nodes have been emptied some other way.
+    pub fn prune_empty(&mut self) {
+        self.prune_with(|_, _| true);
+    }
+
+    /// Remove every entry whose key is longer than `max_depth`
+    ///
+    /// Values below the cutoff are discarded rather than merged into their ancestors, and
+    /// nodes left leading nowhere are pruned, so afterwards [self.height] is at most
+    /// `max_depth`.
+    pub fn truncate_depth(&mut self, max_depth: usize) {
+        self.prune_with(|path, _| path.len() < max_depth);
+    }
+
+    /// Release excess capacity from every node's children
+    ///
+    /// Worth calling on a long-lived Trie that is mostly read once it's built.
+    pub fn shrink_to_fit(&mut self) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            node.children.shrink_to_fit();
+            stack.extend(node.children.iter_mut());
+        }
+    }
+
+    /// Build a Trie with the same keys, holding `f` applied to each value
+    pub fn map<U, G: FnMut(&D) -> U>(&self, mut f: G) -> Trie<K, U> {
+        // built bottom-up a node at a time, as clone is, so a long key can't overflow the stack
+        let mut stack = vec![(self, 0)];
+        let mut mapped: Vec<Trie<K, U>> = vec![];
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            if let Some(child) = node.children.get(*next) {
+                *next += 1;
+                stack.push((child, 0));
+                continue;
+            }
+            stack.pop();
+            let children = mapped.split_off(mapped.len() - node.children.len());
+            mapped.push(Trie {
+                children,
+                key: node.key,
+                data: node.data.as_ref().map(&mut f),
+            });
+        }
+        mapped.pop().expect("the root is always mapped last")
+    }
+
+    /// Build a Trie holding `f` applied to each entry, dropping those it returns `None` for
+    ///
+    /// Nodes which no longer lead to a value are left out, so the result stays pruned.
+    pub fn filter_map_values<U, G: FnMut(&[K], &D) -> Option<U>>(&self, mut f: G) -> Trie<K, U> {
+        let mut path = vec![];
+        // each node with its new value, how many of its children have been pushed so far, and
+        // where the ones that survived start in `mapped`
+        let mut stack = vec![(self, self.data.as_ref().and_then(|data| f(&path, data)), 0, 0)];
+        let mut mapped: Vec<Trie<K, U>> = vec![];
+        while let Some(&mut (node, _, ref mut next, _)) = stack.last_mut() {
+            if let Some(child) = node.children.get(*next) {
+                *next += 1;
+                path.extend(child.key);
+                let data = child.data.as_ref().and_then(|data| f(&path, data));
+                stack.push((child, data, 0, mapped.len()));
+                continue;
+            }
+            let (node, data, _, start) = stack.pop().expect("the loop just looked at it");
+            path.pop();
+            let trie = Trie {
+                children: mapped.split_off(start),
+                key: node.key,
+                data,
+            };
+            // the root is kept even if nothing survived
+            if stack.is_empty() || !trie.is_dead() {
+                mapped.push(trie);
+            }
+        }
+        mapped.pop().expect("the root is always mapped last")
+    }
+
+    /// Rebuild the Trie with `f` applied to every key element
+    ///
+    /// Values move over untouched. If `f` maps two distinct keys to the same one, the value
+    /// from the key that comes later in depth-first order wins.
+    pub fn map_keys<U: PartialEq + Copy, G: Fn(K) -> U>(self, f: G) -> Trie<U, D> {
+        self.into_iter()
+            .map(|(key, data)| (key.into_iter().map(&f).collect(), data))
+            .collect()
+    }
+
+    /// Consume the Trie, yielding every key depth-first
+    pub fn into_keys(self) -> IntoKeys<K, D> {
+        IntoKeys::new(self.into_iter())
+    }
+
+    /// Consume the Trie, yielding every value depth-first
+    pub fn into_values(self) -> IntoValues<K, D> {
+        IntoValues::new(Some(self))
+    }
+
+    /// Collect every complete key starting with `prefix`, depth-first
+    ///
+    /// The same as [self.autocomplete] without a limit; returned keys include the prefix.
+    pub fn words_with_prefix<F: IntoIterator<Item=K>>(&self, prefix: F) -> Vec<Vec<K>> {
+        self.iter_prefix(prefix).map(|(key, _)| key).collect()
+    }
+
+    /// Collect every value whose key starts with `prefix`, depth-first
+    pub fn get_all_with_prefix<F: IntoIterator<Item=K>>(&self, prefix: F) -> Vec<&D> {
+        match self.find_node(prefix.into_iter()) {
+            Some(node) => node.values().collect(),
+            None => vec![],
+        }
+    }
+
+    /// Recursive half of [self.retain_prefixes]; every candidate starts with the `depth`
+    /// elements leading here
+    fn retain_prefixes_node(&mut self, candidates: &[&[K]], depth: usize) {
+        if candidates.iter().any(|prefix| prefix.len() == depth) {
+            return;
+        }
+        self.data = None;
+        self.children.retain_mut(|child| {
+            let child_candidates: Vec<&[K]> = candidates.iter()
+                .filter(|prefix| Some(prefix[depth]) == child.key)
+                .cloned()
+                .collect();
+            if child_candidates.is_empty() {
+                return false;
+            }
+            child.retain_prefixes_node(&child_candidates, depth + 1);
+            !child.is_dead()
+        });
+    }
+
+    /// Construct an empty child node for a key element
+    fn new_node(key_elem: K) -> Trie<K, D> {
+        Trie {
+            children: vec![],
+            key: Some(key_elem),
+            data: None,
+        }
+    }
+
+    /// Move everything out of the Trie, leaving it empty
+    fn take_all(&mut self) -> Trie<K, D> {
+        mem::take(self)
+    }
+
+    /// Shared half of [self.merge] and [self.append]; takes the resolver by reference so both
+    /// can pass their own
+    fn merge_node<G: Fn(D, D) -> D>(&mut self, other: Trie<K, D>, on_conflict: &G) {
+        // pairs of nodes for the same key, one from each Trie, still to be merged
+        let mut stack = vec![(self, other)];
+        while let Some((node, mut other)) = stack.pop() {
+            node.data = match (node.data.take(), other.data.take()) {
+                (Some(existing), Some(incoming)) => Some(on_conflict(existing, incoming)),
+                (existing, incoming) => existing.or(incoming),
+            };
+            // the incoming child matching each existing child, if any; the rest move over whole
+            let mut incoming: Vec<Option<Trie<K, D>>> = vec![];
+            for child in mem::take(&mut other.children) {
+                let key_elem = child.key.expect("child nodes always have a key");
+                match node.child_position(key_elem) {
+                    Some(index) => {
+                        if incoming.len() <= index {
+                            incoming.resize_with(index + 1, || None);
+                        }
+                        incoming[index] = Some(child);
+                    },
+                    None => node.children.push(child),
+                }
+            }
+            stack.extend(node.children.iter_mut()
+                .zip(incoming)
+                .filter_map(|(child, incoming)| incoming.map(|incoming| (child, incoming))));
+        }
+    }
+
+    /// Walk the Trie depth-first, handing `enter` each node and the key leading to it on the way
+    /// down, and dropping every node left leading nowhere on the way back up
+    ///
+    /// Where `enter` returns false the node's children are dropped unvisited. Nodes are taken
+    /// out of their parents while their children are walked, so this needs no recursion.
+    fn prune_with<G: FnMut(&[K], &mut Trie<K, D>) -> bool>(&mut self, mut enter: G) {
+        let mut path = vec![];
+        if !enter(&path, self) {
+            self.children.clear();
+            return;
+        }
+        // each node taken out of the Trie, with its parent's children still to be walked and
+        // where its own surviving children start in `kept`
+        let mut stack = vec![];
+        let mut kept = vec![];
+        let mut pending = mem::take(&mut self.children).into_iter();
+        loop {
+            if let Some(mut child) = pending.next() {
+                path.extend(child.key);
+                if !enter(&path, &mut child) {
+                    child.children.clear();
+                }
+                let grandchildren = mem::take(&mut child.children).into_iter();
+                stack.push((child, mem::replace(&mut pending, grandchildren), kept.len()));
+                continue;
+            }
+            let (mut node, siblings, start) = match stack.pop() {
+                Some(frame) => frame,
+                None => break,
+            };
+            node.children = kept.split_off(start);
+            path.pop();
+            if !node.is_dead() {
+                kept.push(node);
+            }
+            pending = siblings;
+        }
+        self.children = kept;
+    }
+
+    /// Detach the child subtree at the key made of `first` followed by `rest`, pruning any
+    /// nodes on the way down that are left leading nowhere
+    fn detach_child<F: Iterator<Item=K>>(&mut self, first: K, rest: F) -> Option<Trie<K, D>> {
+        let indices = self.child_path(Some(first).into_iter().chain(rest))?;
+        let last = indices.len() - 1;
+        // the deepest ancestor of the subtree that still leads somewhere once it's gone
+        let mut keep = 0;
+        let mut node = &*self;
+        for (depth, &index) in indices[..last].iter().enumerate() {
+            if node.data.is_some() || node.children.len() > 1 {
+                keep = depth;
+            }
+            node = &node.children[index];
+        }
+        if node.data.is_some() || node.children.len() > 1 {
+            keep = last;
+        }
+
+        let mut node = &mut *self;
+        for &index in &indices[..keep] {
+            node = &mut node.children[index];
+        }
+        let mut chain = node.children.remove(indices[keep]);
+        if keep == last {
+            return Some(chain);
+        }
+        // the ancestors between `keep` and the subtree are a dead chain, dropped with it
+        let mut node = &mut chain;
+        for &index in &indices[keep + 1..last] {
+            node = &mut node.children[index];
+        }
+        Some(node.children.remove(indices[last]))
+    }
+
+    /// Remove the value at the end of a path of child indices, as [self.remove_iter] does once
+    /// it has found the path
+    fn remove_path(&mut self, indices: &[usize]) -> Option<D> {
+        // the deepest node on the path that still leads somewhere once the value is gone
+        let mut keep = 0;
+        let mut node = &*self;
+        for (depth, &index) in indices.iter().enumerate() {
+            if node.data.is_some() || node.children.len() > 1 {
+                keep = depth;
+            }
+            node = &node.children[index];
+        }
+        node.data.as_ref()?;
+        if !node.children.is_empty() {
+            keep = indices.len();
+        }
+
+        let mut node = &mut *self;
+        for &index in &indices[..keep] {
+            node = &mut node.children[index];
+        }
+        if keep == indices.len() {
+            return node.data.take();
+        }
+        // everything below `keep` is a chain ending at the value, so cut it off whole
+        let mut chain = node.children.remove(indices[keep]);
+        let mut node = &mut chain;
+        for &index in &indices[keep + 1..] {
+            node = &mut node.children[index];
+        }
+        node.data.take()
+    }
+
+    /// The index of each child along the key, or `None` if the Trie doesn't hold that path
+    fn child_path<F: Iterator<Item=K>>(&self, key_elems: F) -> Option<Vec<usize>> {
+        let mut indices = vec![];
+        let mut node = self;
+        for key_elem in key_elems {
+            let index = node.child_position(key_elem)?;
+            indices.push(index);
+            node = &node.children[index];
+        }
+        Some(indices)
+    }
+
+    /// Walk down the Trie along the key, visiting this node and each one matched after it
+    fn path<F: Iterator<Item=K>>(&self, key_elems: F) -> Path<'_, K, D, F> {
+        Path::new(self, key_elems)
+    }
+
+    /// Walk down the Trie along every element of the key, returning the node it ends at
+    fn find_node<F: Iterator<Item=K>>(&self, key_elems: F) -> Option<&Trie<K, D>> {
+        let mut node = self;
+        for key_elem in key_elems {
+            let index = node.child_position(key_elem)?;
+            node = &node.children[index];
+        }
+        Some(node)
+    }
+
+    /// Mutable counterpart of [self.find_node]
+    fn find_node_mut<F: Iterator<Item=K>>(&mut self, key_elems: F) -> Option<&mut Trie<K, D>> {
+        let mut node = self;
+        for key_elem in key_elems {
+            let index = node.child_position(key_elem)?;
+            node = &mut node.children[index];
+        }
+        Some(node)
+    }
+
+    /// Find the index of the child holding the given key element
+    fn child_position(&self, key_elem: K) -> Option<usize> {
+        self.children.iter().position(|child| child.key == Some(key_elem))
+    }
+
+    /// Find the child holding the given key element, adding it after the others if needed
+    fn child_or_insert(&mut self, key_elem: K) -> &mut Trie<K, D> {
+        let index = match self.child_position(key_elem) {
+            Some(index) => index,
+            None => {
+                self.children.push(Trie::new_node(key_elem));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    /// A node with no data and no children leads nowhere and can be pruned
+    fn is_dead(&self) -> bool {
+        self.data.is_none() && self.children.is_empty()
+    }
+}
+
+impl<K: Ord + Copy, D> Trie<K, D> {
+
+    /// Build a Trie from key/value pairs that are already sorted by key
+    ///
+    /// Since each key sorts after the one before it, it only ever branches off the path of the
+    /// previous key, so new children are pushed on without searching for them. The result is the
+    /// same as collecting the pairs: a repeated key keeps its last value, as with
+    /// [Trie::insert_or_replace], and a pair that's out of order falls back to that insert.
+    pub fn from_sorted<I: IntoIterator<Item=(Vec<K>, D)>>(pairs: I) -> Trie<K, D> {
+        let mut trie = Trie::new_empty();
+        // the largest key so far, with the index of each child along its path
+        let mut last: Option<(Vec<K>, Vec<usize>)> = None;
+        for (key, data) in pairs {
+            let shared = match last {
+                Some((ref prev, _)) if key <= *prev => {
+                    trie.insert_or_replace(key, data);
+                    continue;
+                },
+                Some((ref prev, _)) => prev.iter().zip(&key).take_while(|&(a, b)| a == b).count(),
+                None => 0,
+            };
+            // out-of-order inserts only ever push children, so the indices stay valid
+            let mut path = last.map_or(vec![], |(_, path)| path);
+            path.truncate(shared);
+            let mut node = &mut trie;
+            for &index in &path {
+                node = &mut node.children[index];
+            }
+            for &key_elem in &key[shared..] {
+                path.push(node.children.len());
+                node.children.push(Trie::new_node(key_elem));
+                node = node.children.last_mut().expect("just pushed a child");
+            }
+            node.data = Some(data);
+            last = Some((key, path));
+        }
+        trie
+    }
+
+    /// Collect every key and value, sorted by key
+    ///
+    /// The same for equal Tries however they were built, unlike the order of [self.iter],
+    /// which follows the order children were added in.
+    pub fn to_sorted_vec(&self) -> Vec<(Vec<K>, &D)> {
+        let mut entries: Vec<(Vec<K>, &D)> = self.iter().collect();
+        // keys are unique, so an unstable sort can't reorder anything that compares equal
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    /// The entry with the smallest key, in lexical order
+    ///
+    /// A key sorts before every key it's a prefix of, so this stops at the first value on the
+    /// way down the path of smallest children.
+    pub fn first_key_value(&self) -> Option<(Vec<K>, &D)> {
+        let mut key = vec![];
+        let mut node = self;
+        loop {
+            if let Some(ref data) = node.data {
+                return Some((key, data));
+            }
+            node = node.children.iter().min_by_key(|child| child.key)?;
+            key.extend(node.key);
+        }
+    }
+
+    /// The entry with the largest key, in lexical order
+    ///
+    /// Found at the bottom of the path of largest children, since every leaf holds a value.
+    pub fn last_key_value(&self) -> Option<(Vec<K>, &D)> {
+        let mut key = vec![];
+        let mut node = self;
+        while let Some(child) = node.children.iter().max_by_key(|child| child.key) {
+            node = child;
+            key.extend(node.key);
+        }
+        node.data.as_ref().map(|data| (key, data))
+    }
+
+    /// Collect every entry with a key in `[start, end)`, in lexical order
+    ///
+    /// Subtrees lying wholly before `start` or at or after `end` are skipped without being
+    /// walked.
+    pub fn range<F: IntoIterator<Item=K>>(&self, start: F, end: F) -> Vec<(Vec<K>, &D)> {
+        let start: Vec<K> = start.into_iter().collect();
+        let end: Vec<K> = end.into_iter().collect();
+        let mut found = vec![];
+        let mut path = vec![];
+        self.range_node(&start, &end, &mut path, &mut found);
+        found
+    }
+
+    /// Freeze the Trie into an immutable, flattened [FrozenTrie]
+    ///
+    /// Worth it for a Trie that's queried many times once it's built.
+    pub fn freeze(self) -> FrozenTrie<K, D> {
+        FrozenTrie::from_trie(self)
+    }
+
+    /// Collect the entries at or below this node (reached by `path`) in `[start, end)`
+    fn range_node<'a>(&'a self, start: &[K], end: &[K], path: &mut Vec<K>,
+                      found: &mut Vec<(Vec<K>, &'a D)>) {
+        if let Some(ref data) = self.data {
+            if &path[..] >= start {
+                found.push((path.clone(), data));
+            }
+        }
+        let mut children: Vec<&Trie<K, D>> = self.children.iter().collect();
+        children.sort_unstable_by_key(|child| child.key);
+        for child in children {
+            path.extend(child.key);
+            // every key below the child starts with its path, so once that path reaches `end`
+            // the rest of the children are out of range too
+            if &path[..] >= end {
+                path.pop();
+                break;
+            }
+            // skip a child whose keys all sort before `start`
+            if &path[..] >= start || start.starts_with(path) {
+                child.range_node(start, end, path, found);
+            }
+            path.pop();
+        }
+    }
+}
+
+impl<D> Trie<char, D> {
+
+    /// Insert a new value into the Trie under the characters of a string
+    pub fn insert_str(&mut self, key: &str, data: D) -> ErrType {
+        self.insert(key.chars(), data)
+    }
+
+    /// Search for the longest match in the Trie for a string
+    pub fn search_str(&self, key: &str) -> Option<&D> {
+        self.search(key.chars())
+    }
+
+    /// Look up the value stored at exactly this string
+    pub fn get_str(&self, key: &str) -> Option<&D> {
+        self.get(key.chars())
+    }
+
+    /// Collect every stored string starting with `prefix`, depth-first
+    pub fn strings_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.iter_prefix(prefix.chars()).map(|(key, _)| key.into_iter().collect()).collect()
+    }
+
+    /// Search for the longest match in the Trie, ignoring case
+    ///
+    /// Each query character is tried as given, lowercased and uppercased, against the stored
+    /// keys. Only case mappings to a single character are tried, so e.g. 'ß' won't match a
+    /// stored "SS".
+    pub fn search_ci(&self, key: &str) -> Option<&D> {
+        let query: Vec<char> = key.chars().collect();
+        self.search_ci_from(&query).map(|(_, data)| data)
+    }
+
+    /// The longest case-insensitive match at or below this node, with how much of the query it
+    /// consumed
+    fn search_ci_from(&self, query: &[char]) -> Option<(usize, &D)> {
+        let mut best = self.data.as_ref().map(|data| (0, data));
+        let (first, rest) = match query.split_first() {
+            Some(split) => split,
+            None => return best,
+        };
+        // several case variants may be stored side by side, so try each of them
+        let variants = [
+            Some(*first),
+            single_char(first.to_lowercase()),
+            single_char(first.to_uppercase()),
+        ];
+        for (i, variant) in variants.iter().enumerate() {
+            let variant = match *variant {
+                Some(variant) if !variants[..i].contains(&Some(variant)) => variant,
+                _ => continue,
+            };
+            if let Some(index) = self.child_position(variant) {
+                if let Some((len, data)) = self.children[index].search_ci_from(rest) {
+                    if best.is_none_or(|(best_len, _)| len + 1 > best_len) {
+                        best = Some((len + 1, data));
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+impl<D> Trie<u8, D> {
+
+    /// Insert a new value into the Trie under a byte string
+    pub fn insert_bytes(&mut self, key: &[u8], data: D) -> ErrType {
+        self.insert(key.iter().cloned(), data)
+    }
+
+    /// Search for the longest match in the Trie for a byte string
+    pub fn search_bytes(&self, key: &[u8]) -> Option<&D> {
+        self.search(key.iter().cloned())
+    }
+
+    /// Look up the value stored at exactly this byte string
+    pub fn get_bytes(&self, key: &[u8]) -> Option<&D> {
+        self.get(key.iter().cloned())
+    }
+}
+
+/// The only character in the iterator, or `None` if there are zero or several
+fn single_char<I: Iterator<Item=char>>(mut chars: I) -> Option<char> {
+    let first = chars.next()?;
+    match chars.next() {
+        Some(_) => None,
+        None => Some(first),
+    }
+}
+
+impl<K: Ord + Copy> Trie<K, u64> {
+
+    /// The `k` highest-weighted keys starting with `prefix`, heaviest first
+    ///
+    /// Values are the weights. Only the best `k` seen so far are kept (in a min-heap) while the
+    /// subtree is walked, so all the completions are never sorted. Equal weights are broken by
+    /// key, lexically smallest first.
+    pub fn top_k_completions<F: IntoIterator<Item=K>>(&self, prefix: F, k: usize) -> Vec<(Vec<K>, u64)> {
+        if k == 0 {
+            return vec![];
+        }
+        // the heap's top is the worst candidate kept: the lightest, then the lexically largest
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        for (key, &weight) in self.iter_prefix(prefix) {
+            let better = match best.peek() {
+                Some(&Reverse((worst, Reverse(ref worst_key)))) if best.len() == k => {
+                    weight > worst || (weight == worst && key < *worst_key)
+                },
+                _ => true,
+            };
+            if better {
+                best.push(Reverse((weight, Reverse(key))));
+                if best.len() > k {
+                    best.pop();
+                }
+            }
+        }
+        // ascending order of Reverse is best first
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((weight, Reverse(key)))| (key, weight))
+            .collect()
+    }
+}
+
+impl<K, D> Default for Trie<K, D> {
+
+    /// Construct a new, empty Trie; the same as [Trie::new_empty]
+    fn default() -> Trie<K, D> {
+        Trie {
+            children: vec![],
+            key: None,
+            data: None,
+        }
+    }
+}
+
+impl<K: Clone, D: Clone> Clone for Trie<K, D> {
+
+    /// Copy the Trie a node at a time, building each node once its children are copied, since
+    /// a derived (recursive) clone would overflow the stack on a very long key
+    fn clone(&self) -> Trie<K, D> {
+        // each node with how many of its children have been pushed so far
+        let mut stack = vec![(self, 0)];
+        // copies of finished nodes, waiting to be collected by their parent
+        let mut copies: Vec<Trie<K, D>> = vec![];
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            if let Some(child) = node.children.get(*next) {
+                *next += 1;
+                stack.push((child, 0));
+                continue;
+            }
+            stack.pop();
+            let children = copies.split_off(copies.len() - node.children.len());
+            copies.push(Trie {
+                children,
+                key: node.key.clone(),
+                data: node.data.clone(),
+            });
+        }
+        copies.pop().expect("the root is always copied last")
+    }
+}
+
+impl<K, D> Drop for Trie<K, D> {
+
+    /// Tear the Trie down a node at a time, since the default recursive drop would overflow
+    /// the stack on a very long key
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+impl<K: Copy + fmt::Debug, D: fmt::Debug> fmt::Debug for Trie<K, D> {
+
+    /// `{:?}` prints the entries as a map from whole keys to values, depth-first
+    ///
+    /// `{:#?}` prints the Trie's structure instead: one line per node, indented by depth,
+    /// showing the node's key element and its value if it has one.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !f.alternate() {
+            return f.debug_map().entries(Iter::new(Some(self), vec![])).finish();
+        }
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            write!(f, "{:indent$}", "", indent = depth * 2)?;
+            match node.key {
+                Some(ref key) => write!(f, "{:?}", key)?,
+                None => f.write_str("Trie")?,
+            }
+            if let Some(ref data) = node.data {
+                write!(f, " => {:?}", data)?;
+            }
+            f.write_str("\n")?;
+            stack.extend(node.children.iter().rev().map(|child| (child, depth + 1)));
+        }
+        Ok(())
+    }
+}
+
+impl<K: PartialEq + Copy, D: PartialEq> PartialEq for Trie<K, D> {
+
+    /// Two Tries are equal when they hold the same keys with the same values
+    ///
+    /// The order children happen to be stored in (which depends on insertion order) doesn't
+    /// matter.
+    fn eq(&self, other: &Trie<K, D>) -> bool {
+        // keys are unique, so equal counts plus every entry being found in `other` is enough
+        self.len() == other.len() && self.iter().all(|(key, data)| other.get(key) == Some(data))
+    }
+}
+
+impl<K: Eq + Copy, D: Eq> Eq for Trie<K, D> {}
+
+impl<K: Copy + Hash, D: Hash> Hash for Trie<K, D> {
+
+    /// Hash each entry on its own with a fixed hasher and add the results up, so equal Tries
+    /// hash the same whatever order their children were added in
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0usize;
+        let mut sum = 0u64;
+        for (key, data) in Iter::new(Some(self), vec![]) {
+            let mut entry = FnvHasher::default();
+            key.hash(&mut entry);
+            data.hash(&mut entry);
+            sum = sum.wrapping_add(entry.finish());
+            len += 1;
+        }
+        len.hash(state);
+        sum.hash(state);
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D> Index<&'a [K]> for Trie<K, D> {
+    type Output = D;
+
+    /// Look up the value stored at exactly this key
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key holds no value; use [Trie::get] to avoid that.
+    fn index(&self, key: &'a [K]) -> &D {
+        self.get(key.iter().cloned()).expect("key not found in Trie")
+    }
+}
+
+impl<'a, D> Index<&'a str> for Trie<char, D> {
+    type Output = D;
+
+    /// Look up the value stored at exactly this string
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key holds no value; use [Trie::get_str] to avoid that.
+    fn index(&self, key: &'a str) -> &D {
+        self.get_str(key).expect("key not found in Trie")
+    }
+}
+
+impl<K: Copy, D> IntoIterator for Trie<K, D> {
+    type Item = (Vec<K>, D);
+    type IntoIter = IntoIter<K, D>;
+
+    /// Consume the Trie, yielding every key and value depth-first
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter::new(Some(self), vec![])
+    }
+}
+
+impl<K: PartialEq + Copy, D> FromIterator<(Vec<K>, D)> for Trie<K, D> {
+
+    /// Build a Trie from key/value pairs
+    ///
+    /// Unlike [Trie::insert], a duplicate key isn't an error: the last value given for it wins.
+    fn from_iter<I: IntoIterator<Item=(Vec<K>, D)>>(pairs: I) -> Trie<K, D> {
+        let mut trie = Trie::new_empty();
+        trie.extend(pairs);
+        trie
+    }
+}
+
+impl<K: PartialEq + Copy, D> Extend<(Vec<K>, D)> for Trie<K, D> {
+
+    /// Insert every key/value pair, overwriting the value of any key already present
+    fn extend<I: IntoIterator<Item=(Vec<K>, D)>>(&mut self, pairs: I) {
+        for (key, data) in pairs {
+            self.insert_or_replace(key, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use super::{RcTrie, Trie, TrieError};
+
+    #[test]
+    #[allow(clippy::partialeq_to_none)]
+    fn create_trie() {
+        let mut t = Trie::new_empty();
+
+        assert_eq!(t.insert("abc".chars(), "foobar".to_string()), Ok(()));
+
+        assert_eq!(t.search("ab".chars()), None);
+
+        let res = t.search("abc".chars());
+        assert!(res != None);
+        if let Some(value) = res {
+            assert_eq!(value, "foobar")
+        }
+    }
+
+    #[test]
+    #[allow(clippy::partialeq_to_none)]
+    fn test_longest_match() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abc".chars(), "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert("ab".chars(), "object 2".to_string()), Ok(()));
+
+        let res1 = t.search("abc".chars());
+
+        assert!(res1 != None);
+        if let Some(value) = res1 {
+            assert_eq!(value, "object 1");
+        }
+
+        let res2 = t.search("abcdef".chars());
+
+        assert!(res2 != None);
+        if let Some(value) = res2 {
+            assert_eq!(value, "object 1");
+        }
+
+        let res3 = t.search("ab".chars());
+
+        assert!(res3 != None);
+        if let Some(value) = res3 {
+            assert_eq!(value, "object 2");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
+    fn test_ints_and_vectors() {
+        let mut t: Trie<i32, i32> = Trie::new_empty();
+
+
+        assert_eq!(t.insert(vec![1, 2], 10), Ok(()));
+        assert_eq!(t.insert(vec![1, 2, 3], 20), Ok(()));
+
+        assert_eq!(t.search(vec![1]), None);
+
+        if let Some(value) = t.search(vec![1, 2, 3]) {
+            assert_eq!(value, &20);
+        } else {
+            assert!(false);
+        }
+
+        if let Some(value) = t.search(vec![1, 2, 4]) {
+            assert_eq!(value, &10);
+        } else {
+            assert!(false);
+        }
+    }
+
+    #[test]
+    fn test_double_insert_fails() {
+        let mut t = Trie::new_empty();
+
+        assert!(t.insert("ab".chars(), 1).is_ok());
+        assert!(t.insert("ab".chars(), 1).is_err());
+    }
+
+    #[test]
+    fn test_get_exact_match() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+
+        assert_eq!(t.get("abc".chars()), Some(&1));
+        assert_eq!(t.get("ab".chars()), None);
+        assert_eq!(t.get("abcd".chars()), None);
+        assert_eq!(t.get("".chars()), None);
+
+        // search still falls back to the longest stored prefix
+        assert_eq!(t.search("abcd".chars()), Some(&1));
+        assert_eq!(t.search("ab".chars()), None);
+    }
+
+    #[test]
+    fn test_get_vs_search_nested() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abcd".chars(), 2), Ok(()));
+
+        assert_eq!(t.get("abc".chars()), None);
+        assert_eq!(t.get("abx".chars()), None);
+        assert_eq!(t.search("abx".chars()), Some(&1));
+        assert_eq!(t.get("abcd".chars()), Some(&2));
+        assert_eq!(t.search("abcd".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_contains_key() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.insert("xy".chars(), 2), Ok(()));
+
+        assert!(t.contains_key("abc".chars()));
+        assert!(t.contains_key("xy".chars()));
+        assert!(!t.contains_key("abx".chars()));
+        assert!(!t.contains_key("abcd".chars()));
+        // proper prefixes that were never inserted themselves
+        assert!(!t.contains_key("ab".chars()));
+        assert!(!t.contains_key("".chars()));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert("abc".chars(), "object 2".to_string()), Ok(()));
+
+        if let Some(value) = t.get_mut("abc".chars()) {
+            value.push_str(" changed");
+        } else {
+            panic!("expected a match");
+        }
+
+        assert_eq!(t.get("abc".chars()).map(|v| v.as_str()), Some("object 2 changed"));
+        assert_eq!(t.get("ab".chars()).map(|v| v.as_str()), Some("object 1"));
+        assert!(t.get_mut("a".chars()).is_none());
+        assert!(t.get_mut("abcd".chars()).is_none());
+    }
+
+    #[test]
+    fn test_remove_leaf() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 2), Ok(()));
+
+        assert_eq!(t.remove("abc".chars()), Some(2));
+        assert_eq!(t.search("abc".chars()), Some(&1));
+        assert_eq!(t.remove("abc".chars()), None);
+
+        // the now-empty 'c' node should have been pruned
+        assert_eq!(t.children.len(), 1);
+        assert!(t.children[0].children[0].children.is_empty());
+
+        assert_eq!(t.remove("ab".chars()), Some(1));
+        assert!(t.children.is_empty());
+    }
+
+    #[test]
+    fn test_remove_internal_key() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 2), Ok(()));
+
+        assert_eq!(t.remove("ab".chars()), Some(1));
+        assert_eq!(t.search("ab".chars()), None);
+        assert_eq!(t.search("abc".chars()), Some(&2));
+
+        // the key can be inserted again after removal
+        assert_eq!(t.insert("ab".chars(), 3), Ok(()));
+        assert_eq!(t.search("ab".chars()), Some(&3));
+    }
+
+    #[test]
+    fn test_remove_missing_key() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+
+        assert_eq!(t.remove("ab".chars()), None);
+        assert_eq!(t.remove("abd".chars()), None);
+        assert_eq!(t.remove("xyz".chars()), None);
+        assert_eq!(t.search("abc".chars()), Some(&1));
+    }
+
+    #[test]
+    fn test_len() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.len(), 0);
+
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 2), Ok(()));
+        assert_eq!(t.len(), 2);
+
+        assert_eq!(t.insert("x".chars(), 3), Ok(()));
+        assert_eq!(t.len(), 3);
+
+        assert_eq!(t.remove("ab".chars()), Some(1));
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let mut t = Trie::new_empty();
+        assert!(t.is_empty());
+
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert!(!t.is_empty());
+
+        assert_eq!(t.remove("abc".chars()), Some(1));
+        assert!(t.is_empty());
+
+        // a value stored under the empty key lives on the root itself
+        assert_eq!(t.insert("".chars(), 2), Ok(()));
+        assert!(!t.is_empty());
+    }
+
+    #[test]
+    fn test_is_empty_ignores_dangling_nodes() {
+        let mut t: Trie<char, i32> = Trie::new_empty();
+        t.children.push(Trie::new_node('a'));
+        assert!(t.is_empty());
+        assert_eq!(t.len(), 0);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), 2), Ok(()));
+        assert_eq!(t.insert("".chars(), 3), Ok(()));
+        let capacity = t.children.capacity();
+
+        t.clear();
+        assert!(t.is_empty());
+        assert_eq!(t.search("abc".chars()), None);
+        assert_eq!(t.children.capacity(), capacity);
+
+        assert_eq!(t.insert("abc".chars(), 4), Ok(()));
+        assert_eq!(t.get("abc".chars()), Some(&4));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 2), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), 3), Ok(()));
+
+        let entries: Vec<(String, i32)> = t.iter()
+            .map(|(k, v)| (k.into_iter().collect(), *v))
+            .collect();
+        assert_eq!(entries, vec![
+            ("ab".to_string(), 1),
+            ("abc".to_string(), 2),
+            ("xyz".to_string(), 3),
+        ]);
+    }
+
+    #[test]
+    fn test_to_sorted_vec() {
+        let words = ["tea", "ten", "", "to", "inn", "t"];
+        let mut forwards = Trie::new_empty();
+        for word in words.iter() {
+            assert_eq!(forwards.insert(word.chars(), word.len()), Ok(()));
+        }
+        let mut backwards = Trie::new_empty();
+        for word in words.iter().rev() {
+            assert_eq!(backwards.insert(word.chars(), word.len()), Ok(()));
+        }
+        // a removed branch leaves no trace either
+        assert_eq!(backwards.insert_str("zebra", 5), Ok(()));
+        assert_eq!(backwards.remove("zebra".chars()), Some(5));
+
+        let exported = forwards.to_sorted_vec();
+        assert_eq!(exported, backwards.to_sorted_vec());
+        let keys: Vec<String> = exported.into_iter().map(|(k, _)| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["", "inn", "t", "tea", "ten", "to"]);
+        assert!(Trie::<u8, ()>::new_empty().to_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn test_iter_empty_key_and_empty_trie() {
+        let mut t: Trie<i32, i32> = Trie::new_empty();
+        assert_eq!(t.iter().count(), 0);
+
+        assert_eq!(t.insert(vec![], 1), Ok(()));
+        assert_eq!(t.insert(vec![1], 2), Ok(()));
+        assert_eq!(t.iter().collect::<Vec<_>>(), vec![(vec![], &1), (vec![1], &2)]);
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut t = Trie::new_empty();
+        let words = ["car", "card", "care", "dog"];
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let mut keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        keys.sort();
+        assert_eq!(keys, words.iter().map(|w| w.to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_values() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 2), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 3), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), 4), Ok(()));
+
+        assert_eq!(t.values().count(), t.len());
+        assert_eq!(t.values().sum::<i32>(), 10);
+    }
+
+    #[test]
+    fn test_values_mut() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 2), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 3), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), 4), Ok(()));
+
+        for value in t.values_mut() {
+            *value *= 2;
+        }
+        assert_eq!(t.get("".chars()), Some(&2));
+        assert_eq!(t.get("ab".chars()), Some(&4));
+        assert_eq!(t.get("abc".chars()), Some(&6));
+        assert_eq!(t.get("xyz".chars()), Some(&8));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert("abc".chars(), "object 2".to_string()), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), "object 3".to_string()), Ok(()));
+
+        let mut entries = vec![];
+        for (k, v) in t {
+            entries.push((k.into_iter().collect::<String>(), v));
+        }
+        entries.sort();
+        assert_eq!(entries, vec![
+            ("ab".to_string(), "object 1".to_string()),
+            ("abc".to_string(), "object 2".to_string()),
+            ("xyz".to_string(), "object 3".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let pairs = vec![
+            ("ab".chars().collect(), 1),
+            ("abc".chars().collect(), 2),
+            ("ab".chars().collect(), 3),
+        ];
+        let t: Trie<char, i32> = pairs.into_iter().collect();
+
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.get("ab".chars()), Some(&3));
+        assert_eq!(t.get("abc".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("xy".chars(), 2), Ok(()));
+
+        t.extend(vec![
+            ("abc".chars().collect(), 3),
+            ("xy".chars().collect(), 4),
+            ("z".chars().collect(), 5),
+        ]);
+
+        assert_eq!(t.len(), 4);
+        assert_eq!(t.get("ab".chars()), Some(&1));
+        assert_eq!(t.get("abc".chars()), Some(&3));
+        assert_eq!(t.get("xy".chars()), Some(&4));
+        assert_eq!(t.get("z".chars()), Some(&5));
+    }
+
+    #[test]
+    fn test_default() {
+        let t = Trie::<char, i32>::default();
+        assert!(t.is_empty());
+        assert_eq!(t.len(), 0);
+    }
+
+    #[test]
+    fn test_clone_is_deep() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert("abc".chars(), "object 2".to_string()), Ok(()));
+
+        let mut cloned = t.clone();
+        if let Some(value) = cloned.get_mut("abc".chars()) {
+            value.push_str(" changed");
+        }
+        assert_eq!(cloned.insert("abd".chars(), "object 3".to_string()), Ok(()));
+
+        assert_eq!(t.get("abc".chars()).map(|v| v.as_str()), Some("object 2"));
+        assert_eq!(cloned.get("abc".chars()).map(|v| v.as_str()), Some("object 2 changed"));
+        assert!(!t.contains_key("abd".chars()));
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    fn test_eq_ignores_insertion_order() {
+        let mut t1 = Trie::new_empty();
+        assert_eq!(t1.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t1.insert("abc".chars(), 2), Ok(()));
+        assert_eq!(t1.insert("xyz".chars(), 3), Ok(()));
+
+        let mut t2 = Trie::new_empty();
+        assert_eq!(t2.insert("xyz".chars(), 3), Ok(()));
+        assert_eq!(t2.insert("abc".chars(), 2), Ok(()));
+        assert_eq!(t2.insert("ab".chars(), 1), Ok(()));
+
+        assert_eq!(t1, t2);
+        assert_eq!(Trie::<char, i32>::new_empty(), Trie::new_empty());
+    }
+
+    #[test]
+    fn test_eq_detects_differences() {
+        let mut t1 = Trie::new_empty();
+        assert_eq!(t1.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t1.insert("abc".chars(), 2), Ok(()));
+
+        let mut t2 = t1.clone();
+        assert_eq!(t2.insert("x".chars(), 3), Ok(()));
+        assert!(t1 != t2);
+
+        let mut t3 = t1.clone();
+        assert_eq!(t3.remove("ab".chars()), Some(1));
+        assert_eq!(t3.insert("ab".chars(), 5), Ok(()));
+        assert!(t1 != t3);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut t: Trie<u8, i32> = Trie::with_capacity(256);
+        assert!(t.children.capacity() >= 256);
+        assert!(t.is_empty());
+
+        assert_eq!(t.insert(vec![1, 2], 1), Ok(()));
+        t.reserve(1000);
+        assert!(t.children.capacity() >= 1001);
+        assert_eq!(t.get(vec![1, 2]), Some(&1));
+    }
+
+    #[test]
+    fn test_merge_disjoint() {
+        let mut t1 = Trie::new_empty();
+        assert_eq!(t1.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t1.insert("x".chars(), 2), Ok(()));
+
+        let mut t2 = Trie::new_empty();
+        assert_eq!(t2.insert("abd".chars(), 3), Ok(()));
+        assert_eq!(t2.insert("ab".chars(), 4), Ok(()));
+        assert_eq!(t2.insert("m".chars(), 5), Ok(()));
+
+        t1.merge(t2, |_, _| panic!("no keys overlap"));
+        assert_eq!(t1.len(), 5);
+        assert_eq!(t1.get("abc".chars()), Some(&1));
+        assert_eq!(t1.get("abd".chars()), Some(&3));
+        assert_eq!(t1.search("abz".chars()), Some(&4));
+        let keys: Vec<String> = t1.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["ab", "abc", "abd", "x", "m"]);
+    }
+
+    #[test]
+    fn test_merge_overlapping() {
+        let mut t1 = Trie::new_empty();
+        assert_eq!(t1.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t1.insert("abc".chars(), 2), Ok(()));
+
+        let mut t2 = Trie::new_empty();
+        assert_eq!(t2.insert("abc".chars(), 10), Ok(()));
+        assert_eq!(t2.insert("abcd".chars(), 20), Ok(()));
+
+        let calls = ::std::cell::Cell::new(0);
+        t1.merge(t2, |existing, incoming| {
+            calls.set(calls.get() + 1);
+            existing + incoming
+        });
+        assert_eq!(calls.get(), 1);
+        assert_eq!(t1.len(), 3);
+        assert_eq!(t1.get("ab".chars()), Some(&1));
+        assert_eq!(t1.get("abc".chars()), Some(&12));
+        assert_eq!(t1.get("abcd".chars()), Some(&20));
+    }
+
+    #[test]
+    fn test_height() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.height(), 0);
+
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.height(), 3);
+
+        assert_eq!(t.insert("ax".chars(), 2), Ok(()));
+        assert_eq!(t.insert("zyxwv".chars(), 3), Ok(()));
+        assert_eq!(t.insert("q".chars(), 4), Ok(()));
+        assert_eq!(t.height(), 5);
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let found: Vec<(String, usize)> = t.iter_prefix("car".chars())
+            .map(|(k, v)| (k.into_iter().collect(), *v))
+            .collect();
+        assert_eq!(found, vec![
+            ("car".to_string(), 0),
+            ("card".to_string(), 1),
+            ("care".to_string(), 2),
+        ]);
+
+        assert_eq!(t.iter_prefix("ca".chars()).count(), 4);
+        assert_eq!(t.iter_prefix("".chars()).count(), 5);
+        assert_eq!(t.iter_prefix("cards".chars()).count(), 0);
+        assert_eq!(t.iter_prefix("x".chars()).count(), 0);
+    }
+
+    #[test]
+    fn test_autocomplete() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let complete = |prefix: &str, limit| -> Vec<String> {
+            t.autocomplete(prefix.chars(), limit).into_iter().map(|k| k.into_iter().collect()).collect()
+        };
+        assert_eq!(complete("ca", 2), vec!["car", "card"]);
+        assert_eq!(complete("ca", 10), vec!["car", "card", "care", "cat"]);
+        assert_eq!(complete("d", 10), vec!["dog"]);
+        assert_eq!(complete("ca", 0), Vec::<String>::new());
+        assert_eq!(complete("x", 10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_search_ci() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("Hello".chars(), 1), Ok(()));
+        assert_eq!(t.insert("hel".chars(), 2), Ok(()));
+
+        assert_eq!(t.search_ci("Hello"), Some(&1));
+        assert_eq!(t.search_ci("hello"), Some(&1));
+        assert_eq!(t.search_ci("HELLO"), Some(&1));
+        assert_eq!(t.search_ci("hElLo world"), Some(&1));
+        assert_eq!(t.search_ci("HELP"), Some(&2));
+        assert_eq!(t.search_ci("he"), None);
+
+        // case-sensitive search is unchanged
+        assert_eq!(t.search("hello".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_search_ci_multichar_folds() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("straße".chars(), 1), Ok(()));
+        assert_eq!(t.insert("STRASSE".chars(), 2), Ok(()));
+
+        assert_eq!(t.search_ci("STRAßE"), Some(&1));
+        assert_eq!(t.search_ci("strasse"), Some(&2));
+    }
+
+    #[test]
+    fn test_longest_common_prefix() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.longest_common_prefix(), vec![]);
+
+        assert_eq!(t.insert("flower".chars(), 1), Ok(()));
+        assert_eq!(t.longest_common_prefix(), "flower".chars().collect::<Vec<_>>());
+
+        assert_eq!(t.insert("flow".chars(), 2), Ok(()));
+        assert_eq!(t.longest_common_prefix(), "flow".chars().collect::<Vec<_>>());
+
+        assert_eq!(t.insert("flight".chars(), 3), Ok(()));
+        assert_eq!(t.longest_common_prefix(), vec!['f', 'l']);
+
+        assert_eq!(t.insert("dog".chars(), 4), Ok(()));
+        assert_eq!(t.longest_common_prefix(), vec![]);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert("abc".chars(), "object 2".to_string()), Ok(()));
+        assert_eq!(t.insert("xyz".chars(), "object 3".to_string()), Ok(()));
+
+        let drained: Vec<(String, String)> = t.drain()
+            .map(|(k, v)| (k.into_iter().collect(), v))
+            .collect();
+        assert_eq!(drained, vec![
+            ("ab".to_string(), "object 1".to_string()),
+            ("abc".to_string(), "object 2".to_string()),
+            ("xyz".to_string(), "object 3".to_string()),
+        ]);
+        assert!(t.is_empty());
+
+        // dropping a drain partway through still leaves the Trie empty
+        assert_eq!(t.insert("ab".chars(), "object 4".to_string()), Ok(()));
+        assert_eq!(t.insert("cd".chars(), "object 5".to_string()), Ok(()));
+        assert_eq!(t.drain().next().map(|(_, v)| v), Some("object 4".to_string()));
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut t = Trie::new_empty();
+        let entries = [("a", 1), ("ab", 10), ("abc", 2), ("abd", 20), ("x", 3), ("xy", 4)];
+        for &(word, value) in entries.iter() {
+            assert_eq!(t.insert(word.chars(), value), Ok(()));
+        }
+
+        t.retain(|_, value| *value >= 10);
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.get("ab".chars()), Some(&10));
+        assert_eq!(t.get("abd".chars()), Some(&20));
+        assert_eq!(t.get("a".chars()), None);
+        assert_eq!(t.get("abc".chars()), None);
+        // the whole "x" branch is gone, not just its values
+        assert_eq!(t.children.len(), 1);
+        assert_eq!(t.children[0].children[0].children.len(), 1);
+
+        t.retain(|key, _| key.len() > 2);
+        let keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["abd"]);
+    }
+
+    #[test]
+    fn test_insert_or_replace() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_or_replace("abc".chars(), 1), None);
+        assert_eq!(t.insert_or_replace("ab".chars(), 2), None);
+        assert_eq!(t.insert_or_replace("abc".chars(), 3), Some(1));
+
+        assert_eq!(t.get("abc".chars()), Some(&3));
+        assert_eq!(t.get("ab".chars()), Some(&2));
+        assert_eq!(t.len(), 2);
+
+        // the erroring insert is unchanged
+        assert!(t.insert("abc".chars(), 4).is_err());
+        assert_eq!(t.get("abc".chars()), Some(&3));
+    }
+
+    #[test]
+    fn test_node_count() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.node_count(), 0);
+
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.node_count(), 3);
+        assert_eq!(t.len(), 1);
+
+        // shares "ab", so only one new node
+        assert_eq!(t.insert("abd".chars(), 2), Ok(()));
+        assert_eq!(t.node_count(), 4);
+        // already a node, now terminal too
+        assert_eq!(t.insert("a".chars(), 3), Ok(()));
+        assert_eq!(t.node_count(), 4);
+        assert_eq!(t.insert("".chars(), 4), Ok(()));
+        assert_eq!(t.node_count(), 4);
+        assert_eq!(t.len(), 4);
+    }
+
+    #[test]
+    fn test_words_with_prefix() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let words: Vec<Vec<char>> = ["car", "card", "care"].iter().map(|w| w.chars().collect()).collect();
+        assert_eq!(t.words_with_prefix("car".chars()), words);
+        assert_eq!(t.words_with_prefix("x".chars()), Vec::<Vec<char>>::new());
+
+        assert_eq!(t.strings_with_prefix("ca"), vec!["car", "card", "care", "cat"]);
+        assert_eq!(t.strings_with_prefix("dog"), vec!["dog"]);
+        assert_eq!(t.strings_with_prefix("").len(), 5);
+        assert_eq!(t.strings_with_prefix("dogs"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_prefixes_of() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["a", "ab", "abd", "b", ""].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let found: Vec<(String, usize)> = t.prefixes_of("abcd".chars())
+            .into_iter()
+            .map(|(k, &v)| (k.into_iter().collect(), v))
+            .collect();
+        let expected: Vec<(String, usize)> = vec![
+            ("".to_string(), 4), ("a".to_string(), 0), ("ab".to_string(), 1),
+        ];
+        assert_eq!(found, expected);
+        assert_eq!(t.prefixes_of("abd".chars()).len(), 4);
+        assert_eq!(t.prefixes_of("x".chars()), vec![(vec![], &4)]);
+        assert_eq!(
+            t.prefixes_of("abcd".chars()).into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            t.search_all("abcd".chars())
+        );
+    }
+
+    #[test]
+    fn test_count_leaves() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.count_leaves(), 0);
+        assert_eq!(t.insert("".chars(), 0), Ok(()));
+        assert_eq!(t.count_leaves(), 0);
+
+        for (i, word) in ["car", "card", "care", "cat", "do"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        // "car" is terminal but has children, so only d, e, t and o are leaves
+        assert_eq!(t.count_leaves(), 4);
+        assert_eq!(t.len(), 6);
+
+        t.remove("card".chars());
+        t.remove("care".chars());
+        assert_eq!(t.count_leaves(), 3);
+    }
+
+    #[test]
+    fn test_truncate_depth() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["", "a", "ab", "abc", "abcd", "xyz", "xy"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        t.truncate_depth(2);
+        let keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["", "a", "ab", "xy"]);
+        assert_eq!(t.height(), 2);
+
+        // "q" only has values below the cutoff, so its whole path goes
+        assert_eq!(t.insert("qrs".chars(), 7), Ok(()));
+        t.truncate_depth(2);
+        assert!(!t.contains_prefix("q".chars()));
+
+        t.truncate_depth(5);
+        assert_eq!(t.len(), 4);
+        t.truncate_depth(0);
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.get("".chars()), Some(&0));
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        for prefix in ["", "c", "ca", "car", "card", "cards", "d", "x"].iter() {
+            assert_eq!(t.count_prefix(prefix.chars()), t.iter_prefix(prefix.chars()).count());
+        }
+        assert_eq!(t.count_prefix("car".chars()), 3);
+        assert_eq!(t.count_prefix("x".chars()), 0);
+    }
+
+    #[test]
+    fn test_contains_prefix() {
+        let mut t = Trie::new_empty();
+        assert!(!t.contains_prefix("".chars()));
+        for (i, word) in ["car", "card", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert!(t.contains_prefix("".chars()));
+        assert!(t.contains_prefix("ca".chars()));
+        assert!(t.contains_prefix("car".chars()));
+        assert!(t.contains_prefix("card".chars()));
+        assert!(!t.contains_prefix("cards".chars()));
+        assert!(!t.contains_prefix("cat".chars()));
+        assert!(!t.contains_prefix("x".chars()));
+
+        t.remove("dog".chars());
+        assert!(!t.contains_prefix("d".chars()));
+    }
+
+    #[test]
+    fn test_fanout() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.fanout("".chars()), 0);
+        for (i, word) in ["cat", "car", "cart", "can", "cow", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert_eq!(t.fanout("ca".chars()), 3);
+        assert_eq!(t.fanout("ca".chars()), t.next_elements("ca".chars()).len());
+        assert_eq!(t.fanout("".chars()), 2);
+        assert_eq!(t.fanout("c".chars()), 2);
+        assert_eq!(t.fanout("car".chars()), 1);
+        assert_eq!(t.fanout("cart".chars()), 0);
+        assert_eq!(t.fanout("x".chars()), 0);
+    }
+
+    #[test]
+    fn test_is_proper_prefix() {
+        let mut t = Trie::new_empty();
+        assert!(!t.is_proper_prefix("".chars()));
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+
+        assert!(t.is_proper_prefix("".chars()));
+        assert!(t.is_proper_prefix("a".chars()));
+        assert!(!t.is_proper_prefix("ab".chars()));
+        assert!(t.contains_prefix("ab".chars()));
+        assert!(!t.is_proper_prefix("abc".chars()));
+        assert!(!t.is_proper_prefix("b".chars()));
+
+        assert_eq!(t.insert_str("abc", 2), Ok(()));
+        assert!(t.is_proper_prefix("ab".chars()));
+        t.remove("abc".chars());
+        assert!(!t.is_proper_prefix("ab".chars()));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let mut other = Trie::new_empty();
+        for (i, word) in ["ca", "cart", "", "emu"].iter().enumerate() {
+            assert_eq!(other.insert(word.chars(), i + 10), Ok(()));
+        }
+
+        t.append(other);
+        let entries: Vec<(String, usize)> = t.iter().map(|(k, &v)| (k.into_iter().collect(), v)).collect();
+        assert_eq!(entries, vec![
+            ("".to_string(), 12),
+            ("ca".to_string(), 10),
+            ("car".to_string(), 0),
+            ("cart".to_string(), 11),
+            ("dog".to_string(), 1),
+            ("emu".to_string(), 13),
+        ]);
+        t.append(Trie::new_empty());
+        assert_eq!(t.len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "present in both")]
+    fn test_append_conflict_panics() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("car", 1), Ok(()));
+        let mut other = Trie::new_empty();
+        assert_eq!(other.insert_str("cart", 2), Ok(()));
+        assert_eq!(other.insert_str("car", 3), Ok(()));
+        t.append(other);
+    }
+
+    #[test]
+    fn test_next_elements() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["cat", "car", "can", "dog"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert_eq!(t.next_elements("ca".chars()), vec!['t', 'r', 'n']);
+        assert_eq!(t.next_elements("".chars()), vec!['c', 'd']);
+        assert_eq!(t.next_elements("cat".chars()), Vec::<char>::new());
+        assert_eq!(t.next_elements("x".chars()), Vec::<char>::new());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut t: Trie<char, usize> = Trie::new_empty();
+        t.update("word".chars(), 1, |count| *count += 1);
+        assert_eq!(t.get("word".chars()), Some(&1));
+        t.update("word".chars(), 1, |count| *count += 1);
+        assert_eq!(t.get("word".chars()), Some(&2));
+
+        // a prefix of an existing key is still vacant
+        t.update("wo".chars(), 10, |_| panic!("not called for a vacant key"));
+        assert_eq!(t.get("wo".chars()), Some(&10));
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["ab", "a", "b"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert_eq!(format!("{:?}", t), "{['a']: 1, ['a', 'b']: 0, ['b']: 2}");
+        assert_eq!(format!("{:#?}", t), "Trie\n  'a' => 1\n    'b' => 0\n  'b' => 2\n");
+
+        assert_eq!(t.insert("".chars(), 3), Ok(()));
+        assert!(format!("{:#?}", t).starts_with("Trie => 3\n"));
+        let empty: Trie<char, usize> = Trie::new_empty();
+        assert_eq!(format!("{:?}", empty), "{}");
+    }
+
+    #[test]
+    fn test_prune_empty() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["abc", "abd", "x"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let before = t.node_count();
+        t.prune_empty();
+        assert_eq!(t.node_count(), before);
+
+        // empty nodes directly, leaving a dangling "ab" -> "c" chain and a lone "x"
+        t.find_node_mut("abc".chars()).unwrap().data = None;
+        t.find_node_mut("x".chars()).unwrap().data = None;
+        assert_eq!(t.node_count(), 5);
+        t.prune_empty();
+        assert_eq!(t.node_count(), 3);
+        assert_eq!(t.keys().collect::<Vec<_>>(), vec![vec!['a', 'b', 'd']]);
+        assert!(!t.contains_prefix("x".chars()));
+
+        t.find_node_mut("abd".chars()).unwrap().data = None;
+        t.prune_empty();
+        assert_eq!(t.node_count(), 0);
+    }
+
+    #[test]
+    fn test_first_last_key_value() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.first_key_value(), None);
+        assert_eq!(t.last_key_value(), None);
+
+        for (i, word) in ["banana", "apple", "app", "cherry", "cherries"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        assert_eq!(t.first_key_value(), Some(("app".chars().collect(), &2)));
+        assert_eq!(t.last_key_value(), Some(("cherry".chars().collect(), &3)));
+
+        assert_eq!(t.insert("".chars(), 5), Ok(()));
+        assert_eq!(t.first_key_value(), Some((vec![], &5)));
+        assert_eq!(t.insert("cherryade".chars(), 6), Ok(()));
+        assert_eq!(t.last_key_value(), Some(("cherryade".chars().collect(), &6)));
+        let mut keys: Vec<Vec<char>> = t.keys().collect();
+        keys.sort();
+        assert_eq!(t.first_key_value().map(|(k, _)| k), keys.first().cloned());
+        assert_eq!(t.last_key_value().map(|(k, _)| k), keys.last().cloned());
+    }
+
+    #[test]
+    fn test_range() {
+        let words = ["", "a", "ab", "abc", "abd", "b", "ba", "bad", "bb", "c"];
+        let mut t = Trie::new_empty();
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let range = |start: &str, end: &str| -> Vec<String> {
+            t.range(start.chars().collect::<Vec<_>>(), end.chars().collect())
+                .into_iter()
+                .map(|(k, _)| k.into_iter().collect())
+                .collect()
+        };
+
+        // stored bounds: start is included, end isn't
+        assert_eq!(range("ab", "b"), vec!["ab", "abc", "abd"]);
+        assert_eq!(range("abc", "bad"), vec!["abc", "abd", "b", "ba"]);
+        // bounds that aren't stored
+        assert_eq!(range("aa", "abz"), vec!["ab", "abc", "abd"]);
+        assert_eq!(range("abcd", "bac"), vec!["abd", "b", "ba"]);
+        assert_eq!(range("", "a"), vec![""]);
+        assert_eq!(range("bb", "zzz"), vec!["bb", "c"]);
+        assert_eq!(range("b", "b"), Vec::<String>::new());
+        assert_eq!(range("c", "a"), Vec::<String>::new());
+        assert_eq!(range("", "zzz").len(), words.len());
+
+        assert_eq!(t.range("ab".chars(), "abd".chars()), vec![
+            ("ab".chars().collect(), &2), ("abc".chars().collect(), &3),
+        ]);
+    }
+
+    #[test]
+    fn test_map_keys() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["abc", "ab", "xyz", ""].iter().enumerate() {
+            assert_eq!(t.insert(word.bytes(), i), Ok(()));
+        }
+
+        let chars: Trie<char, usize> = t.map_keys(|b| b as char);
+        assert_eq!(chars.len(), 4);
+        assert_eq!(chars.get("abc".chars()), Some(&0));
+        assert_eq!(chars.get("".chars()), Some(&3));
+        assert_eq!(chars.search("xyzzy".chars()), Some(&2));
+
+        // "ab" and "xy" both collapse onto [0, 1]; "xy" comes later, so it wins
+        let mut t: Trie<char, usize> = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("xy".chars(), 2), Ok(()));
+        let collapsed: Trie<u8, usize> = t.map_keys(|c| if c == 'a' || c == 'x' { 0 } else { 1 });
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed.get(vec![0, 1]), Some(&2));
+
+        // the new key elements only need PartialEq
+        let scaled: Trie<f32, usize> = chars.map_keys(|c| c as u32 as f32 / 2.0);
+        assert_eq!(scaled.get(vec![48.5, 49.0]), Some(&1));
+    }
+
+    #[test]
+    fn test_filter_map_values() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["a", "ab", "abc", "b", "bcd", "c"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let kept = t.filter_map_values(|key, &v| if v >= 2 && key != ['c'] { Some(v * 10) } else { None });
+        let entries: Vec<(String, usize)> = kept.iter().map(|(k, &v)| (k.into_iter().collect(), v)).collect();
+        let expected: Vec<(String, usize)> = vec![
+            ("abc".to_string(), 20), ("b".to_string(), 30), ("bcd".to_string(), 40),
+        ];
+        assert_eq!(entries, expected);
+        assert!(!kept.contains_key("a".chars()));
+        assert!(!kept.contains_prefix("c".chars()));
+        // "a" and "ab" are gone, but still lead to "abc"; "c" was pruned away entirely
+        assert_eq!(kept.node_count(), 6);
+
+        assert!(t.filter_map_values(|_, _| None::<usize>).is_empty());
+        assert_eq!(t.filter_map_values(|_, _| None::<usize>).node_count(), 0);
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<T: Hash>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let words = ["car", "ca", "cart", "dog", ""];
+        let mut forward = Trie::new_empty();
+        for word in words.iter() {
+            assert_eq!(forward.insert(word.chars(), word.len()), Ok(()));
+        }
+        let mut backward = Trie::new_empty();
+        for word in words.iter().rev() {
+            assert_eq!(backward.insert(word.chars(), word.len()), Ok(()));
+        }
+        assert_eq!(forward, backward);
+        assert_eq!(hash_of(&forward), hash_of(&backward));
+
+        let mut emptied = forward.clone();
+        emptied.insert("x".chars(), 1).unwrap();
+        emptied.remove("x".chars());
+        assert_eq!(hash_of(&emptied), hash_of(&forward));
+
+        emptied.remove("dog".chars());
+        assert_ne!(hash_of(&emptied), hash_of(&forward));
+
+        // key elements only need to be hashable, not ordered
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        enum Base { A, C }
+        let mut ac = Trie::new_empty();
+        assert_eq!(ac.insert(vec![Base::A, Base::C], 1), Ok(()));
+        assert_eq!(ac.insert(vec![Base::C], 2), Ok(()));
+        let mut ca = Trie::new_empty();
+        assert_eq!(ca.insert(vec![Base::C], 2), Ok(()));
+        assert_eq!(ca.insert(vec![Base::A, Base::C], 1), Ok(()));
+        assert_eq!(hash_of(&ac), hash_of(&ca));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut by_prefix: Trie<char, Vec<&str>> = Trie::new_empty();
+        for word in ["cat", "car", "cart", "dog"].iter() {
+            let chars: Vec<char> = word.chars().collect();
+            for len in 1..chars.len() + 1 {
+                by_prefix.get_or_insert_with(chars[..len].iter().cloned(), Vec::new).push(word);
+            }
+        }
+
+        assert_eq!(by_prefix.get("ca".chars()), Some(&vec!["cat", "car", "cart"]));
+        assert_eq!(by_prefix.get("car".chars()), Some(&vec!["car", "cart"]));
+        assert_eq!(by_prefix.get("d".chars()), Some(&vec!["dog"]));
+
+        // an existing value is returned as is, without calling `f`
+        let value = by_prefix.get_or_insert_with("dog".chars(), || panic!("already present"));
+        value.push("dogs");
+        assert_eq!(by_prefix.get("dog".chars()), Some(&vec!["dog", "dogs"]));
+    }
+
+    #[test]
+    fn test_retain_prefixes() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["", "a", "ap", "app", "apple", "apt", "banana", "band", "bat", "cherry"]
+                .iter()
+                .enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        t.retain_prefixes(&["app".chars().collect(), "ban".chars().collect()]);
+        let keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["app", "apple", "banana", "band"]);
+        assert!(!t.contains_prefix("c".chars()));
+        // a-p-p-l-e and b-a-n-a-n-a plus the d of "band"
+        assert_eq!(t.node_count(), 12);
+
+        // a whitelist entry covering another changes nothing
+        t.retain_prefixes(&["a".chars().collect(), "apple".chars().collect(), "band".chars().collect()]);
+        let keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["app", "apple", "band"]);
+
+        t.retain_prefixes(&[vec![]]);
+        assert_eq!(t.len(), 3);
+        t.retain_prefixes(&[]);
+        assert!(t.is_empty());
+        assert_eq!(t.node_count(), 0);
+    }
+
+    #[test]
+    fn test_insert_many() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("b".chars(), 0), Ok(()));
+
+        let batch: Vec<(Vec<char>, usize)> = ["a", "b", "ab", "a", "c"].iter()
+            .enumerate()
+            .map(|(i, word)| (word.chars().collect(), i + 1))
+            .collect();
+        let failed = t.insert_many(batch);
+        assert_eq!(failed, vec![(1, TrieError::KeyAlreadyPresent), (3, TrieError::KeyAlreadyPresent)]);
+
+        assert_eq!(t.len(), 4);
+        // the first value for each key stays
+        assert_eq!(t.get("a".chars()), Some(&1));
+        assert_eq!(t.get("b".chars()), Some(&0));
+        assert_eq!(t.get("ab".chars()), Some(&3));
+        assert_eq!(t.get("c".chars()), Some(&5));
+
+        assert_eq!(t.insert_many(vec![]), vec![]);
+    }
+
+    #[test]
+    fn test_remove_prefix() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["app", "apple", "application", "apt", "banana"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        assert_eq!(t.remove_prefix("app".chars()), 3);
+        assert_eq!(t.len(), 2);
+        assert!(!t.contains_key("apple".chars()));
+        assert_eq!(t.get("apt".chars()), Some(&3));
+        assert_eq!(t.get("banana".chars()), Some(&4));
+
+        // nothing under these prefixes, so nothing changes
+        assert_eq!(t.remove_prefix("app".chars()), 0);
+        assert_eq!(t.remove_prefix("bananas".chars()), 0);
+        assert_eq!(t.len(), 2);
+
+        // removing the only key under a branch prunes the branch
+        assert_eq!(t.remove_prefix("apt".chars()), 1);
+        assert_eq!(t.children.len(), 1);
+
+        assert_eq!(t.remove_prefix("".chars()), 1);
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_take_subtree() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["app", "apple", "application", "apt", "banana"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let sub = t.take_subtree("app".chars());
+        assert!(sub.is_some());
+        let sub = sub.unwrap();
+        let keys: Vec<String> = sub.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["", "le", "lication"]);
+        assert_eq!(sub.get("le".chars()), Some(&1));
+
+        assert_eq!(t.len(), 2);
+        assert!(!t.contains_key("app".chars()));
+        assert!(!t.contains_key("apple".chars()));
+        assert!(t.contains_key("apt".chars()));
+
+        assert!(t.take_subtree("app".chars()).is_none());
+        assert!(t.take_subtree("x".chars()).is_none());
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_bfs() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["abc", "x", "ab", "a"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let entries: Vec<(String, usize)> = t.iter_bfs()
+            .map(|(k, &v)| (k.into_iter().collect(), v))
+            .collect();
+        let expected: Vec<(String, usize)> = vec![
+            ("a".to_string(), 3), ("x".to_string(), 1), ("ab".to_string(), 2), ("abc".to_string(), 0),
+        ];
+        assert_eq!(entries, expected);
+
+        let empty: Trie<char, usize> = Trie::new_empty();
+        assert_eq!(empty.iter_bfs().count(), 0);
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let mut words = vec!["", "a", "ab", "abc", "abd", "b", "ba", "bad", "c", "ca"];
+        let naive: Trie<char, usize> = {
+            let mut t = Trie::new_empty();
+            for (i, word) in words.iter().enumerate() {
+                assert_eq!(t.insert(word.chars(), i), Ok(()));
+            }
+            t
+        };
+        let pairs: Vec<(Vec<char>, usize)> = words.iter()
+            .enumerate()
+            .map(|(i, word)| (word.chars().collect(), i))
+            .collect();
+        let sorted = Trie::from_sorted(pairs.clone());
+        assert_eq!(sorted, naive);
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), naive.keys().collect::<Vec<_>>());
+
+        // repeats keep the last value, and out-of-order pairs still land in the right place
+        let mut messy = pairs.clone();
+        messy.push(("ab".chars().collect(), 100));
+        messy.push(("aa".chars().collect(), 200));
+        messy.push(("ca".chars().collect(), 300));
+        words.push("aa");
+        let collected: Trie<char, usize> = messy.iter().cloned().collect();
+        let messy = Trie::from_sorted(messy);
+        assert_eq!(messy, collected);
+        assert_eq!(messy.len(), words.len());
+        assert_eq!(messy.get("ab".chars()), Some(&100));
+        assert_eq!(messy.get("aa".chars()), Some(&200));
+        assert_eq!(messy.get("ca".chars()), Some(&300));
+        let mut keys: Vec<String> = messy.keys().map(|k| k.into_iter().collect()).collect();
+        keys.sort();
+        let mut expected: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["ap", "app", "apple", "application", "apt", "banana"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let original = t.clone();
+
+        let split = t.split_off("app".chars());
+        let keys: Vec<String> = split.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["app", "apple", "application"]);
+        assert_eq!(split.get("apple".chars()), Some(&2));
+        let keys: Vec<String> = t.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["ap", "apt", "banana"]);
+
+        t.merge(split, |_, _| panic!("split halves share no keys"));
+        assert_eq!(t, original);
+
+        assert!(t.split_off("x".chars()).is_empty());
+        assert_eq!(t, original);
+
+        let everything = t.split_off(vec![]);
+        assert!(t.is_empty());
+        assert_eq!(everything, original);
+    }
+
+    #[test]
+    fn test_clone_subtree() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["app", "apple", "application", "apt", "banana"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let original = t.clone();
+
+        let mut sub = t.clone_subtree("app".chars()).unwrap();
+        let keys: Vec<String> = sub.keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["", "le", "lication"]);
+        assert_eq!(t, original);
+
+        // the copy and the original change independently
+        *sub.get_mut("le".chars()).unwrap() = 100;
+        assert_eq!(sub.insert("ly".chars(), 101), Ok(()));
+        assert_eq!(t.get("apple".chars()), Some(&1));
+        assert!(!t.contains_key("apply".chars()));
+        t.remove("app".chars());
+        assert_eq!(sub.get("".chars()), Some(&0));
+
+        assert_eq!(t.clone_subtree("".chars()).map(|s| s.len()), Some(4));
+        assert!(t.clone_subtree("x".chars()).is_none());
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        fn max_slack<K, D>(t: &Trie<K, D>) -> usize {
+            let here = t.children.capacity() - t.children.len();
+            t.children.iter().map(max_slack).fold(here, ::std::cmp::max)
+        }
+
+        let mut t = Trie::with_capacity(1000);
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abd".chars(), 2), Ok(()));
+        assert_eq!(t.insert("x".chars(), 3), Ok(()));
+        assert!(max_slack(&t) >= 998);
+
+        t.shrink_to_fit();
+        assert_eq!(max_slack(&t), 0);
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.get("abd".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_double_insert_error_kind() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+
+        match t.insert("ab".chars(), 2) {
+            Err(TrieError::KeyAlreadyPresent) => {},
+            other => panic!("expected KeyAlreadyPresent, got {:?}", other),
+        }
+        assert_eq!(t.get("ab".chars()), Some(&1));
+        assert_eq!(TrieError::KeyAlreadyPresent.to_string(), "key already present");
+    }
+
+    #[test]
+    fn test_str_helpers() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        assert_eq!(t.insert_str("abc", 2), Ok(()));
+        assert_eq!(t.insert_str("ab", 3), Err(TrieError::KeyAlreadyPresent));
+
+        assert_eq!(t.get_str("ab"), Some(&1));
+        assert_eq!(t.get_str("abcd"), None);
+        assert_eq!(t.search_str("abcd"), Some(&2));
+        assert_eq!(t.get("abc".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_bytes_helpers() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_bytes(b"ab", 1), Ok(()));
+        assert_eq!(t.insert_bytes(&[0xff, 0x00], 2), Ok(()));
+
+        assert_eq!(t.get_bytes(b"ab"), Some(&1));
+        assert_eq!(t.get_bytes(b"a"), None);
+        assert_eq!(t.search_bytes(b"abc"), Some(&1));
+        assert_eq!(t.search_bytes(&[0xff, 0x00, 0x01]), Some(&2));
+        assert_eq!(t.get("ab".bytes()), Some(&1));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        assert_eq!(t.insert_str("abc", 2), Ok(()));
+
+        assert_eq!(t["ab"], 1);
+        assert_eq!(t["abc"], 2);
+        assert_eq!(t[&['a', 'b'][..]], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "key not found in Trie")]
+    fn test_index_missing_key() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        // longest-prefix matching doesn't apply here
+        let _ = t["abc"];
+    }
+
+    #[test]
+    fn test_search_mut() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("/api", 0), Ok(()));
+        assert_eq!(t.insert_str("/api/users", 0), Ok(()));
+
+        for path in &["/api/users/7", "/api/users", "/api/items", "/apix"] {
+            if let Some(hits) = t.search_mut(path.chars()) {
+                *hits += 1;
+            }
+        }
+        assert_eq!(t.search("/api/users".chars()), Some(&2));
+        assert_eq!(t.search("/api".chars()), Some(&2));
+        assert_eq!(t.search_mut("/ap".chars()), None);
+        assert_eq!(t.search_mut("/".chars()), None);
+
+        assert_eq!(t.insert_str("", 10), Ok(()));
+        *t.search_mut("/ap".chars()).unwrap() += 1;
+        assert_eq!(t.search("".chars()), Some(&11));
+    }
+
+    #[test]
+    fn test_path_values() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("a", 1), Ok(()));
+        assert_eq!(t.insert_str("abc", 3), Ok(()));
+
+        assert_eq!(t.path_values("abcd".chars()), vec![None, Some(&1), None, Some(&3)]);
+        assert_eq!(t.path_values("abx".chars()), vec![None, Some(&1), None]);
+        assert_eq!(t.path_values("abx".chars()).len(), t.common_prefix_len("abx".chars()) + 1);
+        assert_eq!(t.path_values("x".chars()), vec![None]);
+
+        assert_eq!(t.insert_str("", 0), Ok(()));
+        assert_eq!(t.path_values("".chars()), vec![Some(&0)]);
+        assert_eq!(t.path_values("ab".chars()), vec![Some(&0), Some(&1), None]);
+    }
+
+    #[test]
+    fn test_shortest_match() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("a", 1), Ok(()));
+        assert_eq!(t.insert_str("ab", 2), Ok(()));
+        assert_eq!(t.insert_str("abcd", 4), Ok(()));
+
+        assert_eq!(t.shortest_match("abc".chars()), Some(&1));
+        assert_eq!(t.search("abc".chars()), Some(&2));
+        assert_eq!(t.shortest_match("abcde".chars()), Some(&1));
+        assert_eq!(t.search("abcde".chars()), Some(&4));
+        assert_eq!(t.shortest_match("b".chars()), None);
+
+        assert_eq!(t.insert_str("", 0), Ok(()));
+        assert_eq!(t.shortest_match("abc".chars()), Some(&0));
+        assert_eq!(t.shortest_match("b".chars()), Some(&0));
+    }
+
+    #[test]
+    fn test_top_k_completions() {
+        let mut t: Trie<char, u64> = Trie::new_empty();
+        let words = [("car", 50), ("card", 10), ("care", 80), ("cart", 50), ("cat", 90), ("dog", 100)];
+        for &(word, weight) in words.iter() {
+            assert_eq!(t.insert(word.chars(), weight), Ok(()));
+        }
+        let top = |prefix: &str, k| -> Vec<(String, u64)> {
+            t.top_k_completions(prefix.chars(), k)
+                .into_iter()
+                .map(|(key, weight)| (key.into_iter().collect(), weight))
+                .collect()
+        };
+
+        assert_eq!(top("ca", 2), vec![("cat".to_string(), 90), ("care".to_string(), 80)]);
+        // "car" and "cart" tie; the lexically smaller comes first, and wins the last slot
+        assert_eq!(top("ca", 3), vec![
+            ("cat".to_string(), 90), ("care".to_string(), 80), ("car".to_string(), 50),
+        ]);
+        assert_eq!(top("car", 10), vec![
+            ("care".to_string(), 80), ("car".to_string(), 50), ("cart".to_string(), 50),
+            ("card".to_string(), 10),
+        ]);
+        assert_eq!(top("", 1), vec![("dog".to_string(), 100)]);
+        assert_eq!(top("ca", 0), vec![]);
+        assert_eq!(top("x", 3), vec![]);
+    }
+
+    #[test]
+    fn test_common_prefix_len() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.common_prefix_len("abc".chars()), 0);
+        assert_eq!(t.insert_str("abc", 1), Ok(()));
+        assert_eq!(t.insert_str("b", 2), Ok(()));
+
+        assert_eq!(t.common_prefix_len("abc".chars()), 3);
+        assert_eq!(t.common_prefix_len("abcdef".chars()), 3);
+        assert_eq!(t.common_prefix_len("abxyz".chars()), 2);
+        assert_eq!(t.common_prefix_len("bcd".chars()), 1);
+        assert_eq!(t.common_prefix_len("xyz".chars()), 0);
+        assert_eq!(t.common_prefix_len("".chars()), 0);
+    }
+
+    #[test]
+    fn test_search_all() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("a", 1), Ok(()));
+        assert_eq!(t.insert_str("abc", 3), Ok(()));
+        assert_eq!(t.insert_str("ab", 2), Ok(()));
+        assert_eq!(t.insert_str("abd", 4), Ok(()));
+        assert_eq!(t.insert_str("abcde", 5), Ok(()));
+
+        assert_eq!(t.search_all("abcd".chars()), vec![&1, &2, &3]);
+        assert_eq!(t.search_all("abcdef".chars()), vec![&1, &2, &3, &5]);
+        assert_eq!(t.search_all("ab".chars()), vec![&1, &2]);
+        assert_eq!(t.search_all("x".chars()), Vec::<&i32>::new());
+
+        assert_eq!(t.insert_str("", 0), Ok(()));
+        assert_eq!(t.search_all("ax".chars()), vec![&0, &1]);
+    }
+
+    #[test]
+    fn test_map() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        assert_eq!(t.insert_str("abc", 3), Ok(()));
+        assert_eq!(t.insert_str("x", 4), Ok(()));
+
+        let total: i32 = t.values().sum();
+        let mapped: Trie<char, String> = t.map(|count| format!("{:.2}", *count as f64 / total as f64));
+
+        assert_eq!(mapped.len(), 3);
+        assert_eq!(mapped.get_str("ab").map(|v| v.as_str()), Some("0.12"));
+        assert_eq!(mapped.get_str("x").map(|v| v.as_str()), Some("0.50"));
+        assert_eq!(mapped.get_str("a"), None);
+        // the source is untouched
+        assert_eq!(t.get_str("abc"), Some(&3));
+    }
+
+    #[test]
+    fn test_into_keys_and_values() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", "object 1".to_string()), Ok(()));
+        assert_eq!(t.insert_str("abc", "object 2".to_string()), Ok(()));
+        assert_eq!(t.insert_str("", "object 0".to_string()), Ok(()));
+        assert_eq!(t.insert_str("xyz", "object 3".to_string()), Ok(()));
+
+        let keys: Vec<String> = t.clone().into_keys().map(|k| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["", "ab", "abc", "xyz"]);
+
+        let values: Vec<String> = t.into_values().collect();
+        assert_eq!(values, vec!["object 0", "object 1", "object 2", "object 3"]);
+    }
+
+    #[test]
+    fn test_search_falls_back_past_dead_end() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("a".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abc".chars(), 3), Ok(()));
+
+        // the descent reaches the empty "ab" node before it runs out of matches
+        assert_eq!(t.search("abd".chars()), Some(&1));
+        assert_eq!(t.search("ab".chars()), Some(&1));
+        assert_eq!(t.search("abcd".chars()), Some(&3));
+        assert_eq!(t.search("x".chars()), None);
+    }
+
+    #[test]
+    fn test_search_with_len() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        assert_eq!(t.insert_str("abcde", 2), Ok(()));
+
+        assert_eq!(t.search_with_len("abcd".chars()), Some((2, &1)));
+        assert_eq!(t.search_with_len("ab".chars()), Some((2, &1)));
+        assert_eq!(t.search_with_len("abcdef".chars()), Some((5, &2)));
+        assert_eq!(t.search_with_len("a".chars()), None);
+
+        assert_eq!(t.insert_str("", 0), Ok(()));
+        assert_eq!(t.search_with_len("xyz".chars()), Some((0, &0)));
+    }
+
+    #[test]
+    fn test_get_all_with_prefix() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "dog"].iter().enumerate() {
+            assert_eq!(t.insert_str(word, i), Ok(()));
+        }
+
+        assert_eq!(t.get_all_with_prefix("car".chars()), vec![&0, &1, &2]);
+        for prefix in ["", "c", "ca", "car", "card", "cards", "d", "x"].iter() {
+            assert_eq!(t.get_all_with_prefix(prefix.chars()).len(), t.count_prefix(prefix.chars()));
+        }
+    }
+
+    #[test]
+    fn test_very_long_key() {
+        let len = 100_000;
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert((0..len).map(|i| i % 7), "long"), Ok(()));
+        assert_eq!(t.insert((0..10).map(|i| i % 7), "short"), Ok(()));
+
+        assert_eq!(t.search((0..len + 5).map(|i| i % 7)), Some(&"long"));
+        assert_eq!(t.search((0..len - 1).map(|i| i % 7)), Some(&"short"));
+        assert_eq!(t.get((0..len).map(|i| i % 7)), Some(&"long"));
+        assert!(t.insert((0..len).map(|i| i % 7), "again").is_err());
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.height(), len);
+        assert!(!t.is_empty());
+
+        let copy = t.clone();
+        assert_eq!(copy.get((0..len).map(|i| i % 7)), Some(&"long"));
+        assert_eq!(copy.len(), 2);
+        assert_eq!(copy.height(), len);
+
+        assert_eq!(t.remove((0..len).map(|i| i % 7)), Some("long"));
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.height(), 10);
+        assert_eq!(t.get((0..10).map(|i| i % 7)), Some(&"short"));
+
+        let mut copy = copy;
+        let subtree = copy.take_subtree((0..len / 2).map(|i| i % 7)).unwrap();
+        assert_eq!(subtree.height(), len - len / 2);
+        assert_eq!(copy.len(), 1);
+        assert_eq!(copy.height(), 10);
+    }
+
+    #[test]
+    fn test_very_long_key_whole_trie_walks() {
+        let len = 100_000;
+        let long = || (0..len).map(|i| i % 7);
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert(long(), "long"), Ok(()));
+        assert_eq!(t.insert((0..10).map(|i| i % 7), "short"), Ok(()));
+
+        assert_eq!(t.node_count(), len);
+        assert_eq!(t.count_leaves(), 1);
+        t.shrink_to_fit();
+        t.prune_empty();
+        assert_eq!(t.len(), 2);
+
+        let lens = t.map(|word| word.len());
+        assert_eq!(lens.get(long()), Some(&4));
+        let only_long = t.filter_map_values(|key, &word| {
+            if key.len() > 10 { Some(word) } else { None }
+        });
+        assert_eq!(only_long.len(), 1);
+        assert_eq!(only_long.height(), len);
+
+        let mut retained = t.clone();
+        retained.retain(|key, _| key.len() <= 10);
+        assert_eq!(retained.len(), 1);
+        assert_eq!(retained.height(), 10);
+        let mut truncated = t.clone();
+        truncated.truncate_depth(len / 2);
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(truncated.height(), 10);
+
+        let mut merged = Trie::new_empty();
+        assert_eq!(merged.insert((0..len - 1).map(|i| i % 7), "nearly"), Ok(()));
+        assert_eq!(merged.insert(long(), "other"), Ok(()));
+        merged.merge(t, |existing, _| existing);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get(long()), Some(&"other"));
+        assert_eq!(merged.node_count(), len);
+
+        let mut rc = RcTrie::new_empty();
+        assert_eq!(rc.insert(long(), "long"), Ok(()));
+        assert_eq!(rc.insert((0..10).map(|i| i % 7), "short"), Ok(()));
+        let snapshot = rc.snapshot();
+        assert_eq!(rc.remove(long()), Some("long"));
+        assert_eq!(rc.len(), 1);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.get(long()), Some(&"long"));
     }
 }
@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use Trie;
+
+/// A Trie which can hold any number of values under each key
+///
+/// Inserting under a key that's already present appends to its values instead of failing, which
+/// suits inverted indexes. Values under a key are kept in insertion order.
+#[derive(Clone)]
+pub struct MultiTrie<K, D> {
+    inner: Trie<K, Vec<D>>,
+}
+
+impl<K: PartialEq + Copy, D> MultiTrie<K, D> {
+
+    /// Construct a new, empty MultiTrie
+    pub fn new_empty() -> MultiTrie<K, D> {
+        MultiTrie {
+            inner: Trie::new_empty(),
+        }
+    }
+
+    /// Add a value under a key, after any values already there
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) {
+        self.inner.entry(key).or_default().push(data);
+    }
+
+    /// Look up every value stored at exactly this key, in insertion order
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&[D]> {
+        self.inner.get(key).map(|values| &values[..])
+    }
+
+    /// Remove a key, returning all of its values
+    pub fn remove<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<Vec<D>> {
+        self.inner.remove(key)
+    }
+
+    /// Count the distinct keys stored
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Check whether the MultiTrie holds no values at all
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// The underlying Trie, from each key to its values
+    pub fn as_trie(&self) -> &Trie<K, Vec<D>> {
+        &self.inner
+    }
+
+    /// Consume the MultiTrie, returning the underlying Trie
+    pub fn into_trie(self) -> Trie<K, Vec<D>> {
+        self.inner
+    }
+}
+
+impl<K, D> Default for MultiTrie<K, D> {
+
+    fn default() -> MultiTrie<K, D> {
+        MultiTrie {
+            inner: Trie::default(),
+        }
+    }
+}
+
+impl<K: Copy + fmt::Debug, D: fmt::Debug> fmt::Debug for MultiTrie<K, D> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiTrie;
+
+    #[test]
+    fn test_multi_trie() {
+        let mut index = MultiTrie::new_empty();
+        assert!(index.is_empty());
+        for (doc, text) in ["the cat", "a cat sat", "the dog", "cat"].iter().enumerate() {
+            for word in text.split(' ') {
+                index.insert(word.chars(), doc);
+            }
+        }
+
+        assert_eq!(index.get("cat".chars()), Some(&[0, 1, 3][..]));
+        assert_eq!(index.get("the".chars()), Some(&[0, 2][..]));
+        assert_eq!(index.get("sat".chars()), Some(&[1][..]));
+        assert_eq!(index.get("ca".chars()), None);
+        assert_eq!(index.len(), 5);
+
+        assert_eq!(index.remove("the".chars()), Some(vec![0, 2]));
+        assert_eq!(index.get("the".chars()), None);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.as_trie().get("dog".chars()), Some(&vec![2]));
+    }
+
+    #[test]
+    fn test_multi_trie_debug() {
+        let mut t = MultiTrie::new_empty();
+        t.insert("a".chars(), 1);
+        t.insert("a".chars(), 2);
+        assert_eq!(format!("{:?}", t), "{['a']: [1, 2]}");
+    }
+}
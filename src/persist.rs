@@ -0,0 +1,397 @@
+//! Saving and loading byte-keyed Tries
+//!
+//! Only `Trie<u8, Vec<u8>>` (and a [TriePatch] for one) is covered, which keeps the formats
+//! simple: every key and value is just a run of bytes.
+
+use std::io::{self, Read, Write};
+
+use {Trie, TriePatch};
+
+impl Trie<u8, Vec<u8>> {
+
+    /// Write every entry as a length-prefixed record, depth-first, returning how many were
+    /// written
+    ///
+    /// Each record is the key's length as a little-endian `u32`, the key, then the same for the
+    /// value. Read them back with [Trie::load_from_reader].
+    pub fn dump_to_writer<W: Write>(&self, mut w: W) -> io::Result<usize> {
+        let mut written = 0;
+        for (key, value) in self.iter() {
+            write_bytes(&mut w, &key)?;
+            write_bytes(&mut w, value)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Insert every record from a stream written by [Trie::dump_to_writer], returning how many
+    /// were read
+    ///
+    /// Records are inserted as they're read, so the stream is never held in memory all at once;
+    /// wrap an unbuffered reader (like a `File`) in a `BufReader`. A key that's already present
+    /// has its value replaced. The stream must end on a record boundary, or this fails with
+    /// `UnexpectedEof` (keeping the records inserted before that point).
+    pub fn load_from_reader<R: Read>(&mut self, mut r: R) -> io::Result<usize> {
+        let mut loaded = 0;
+        while let Some(key) = read_bytes(&mut r)? {
+            let value = read_bytes(&mut r)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "record has no value"))?;
+            self.insert_or_replace(key, value);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Write the Trie's structure, so [Trie::read_tree] can rebuild it in one pass
+    ///
+    /// Nodes are written depth-first: a flags byte (bit 0 set if the node holds a value), the
+    /// value as a length-prefixed record if it has one, the number of children as a
+    /// little-endian `u16`, and then each child's key byte followed by the child itself. Shared
+    /// prefixes are only written once, so it's smaller than [Trie::dump_to_writer]'s records
+    /// when keys share long prefixes.
+    pub fn write_tree<W: Write>(&self, mut w: W) -> io::Result<()> {
+        // walked with an explicit stack of each open node's remaining children, so a long key
+        // can't overflow the call stack
+        self.write_node(&mut w)?;
+        let mut stack = vec![self.children.iter()];
+        while let Some(children) = stack.last_mut() {
+            match children.next() {
+                Some(child) => {
+                    w.write_all(&[child.key.expect("child nodes always have a key")])?;
+                    child.write_node(&mut w)?;
+                    stack.push(child.children.iter());
+                },
+                None => {
+                    stack.pop();
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuild a Trie written by [Trie::write_tree]
+    ///
+    /// Fails with `InvalidData` if the stream isn't a well-formed tree: unknown flags, two
+    /// children with the same key, or a node which leads to no value.
+    pub fn read_tree<R: Read>(mut r: R) -> io::Result<Trie<u8, Vec<u8>>> {
+        // each node under construction, with how many of its children are still to come
+        let mut stack = vec![Trie::read_node(&mut r, None)?];
+        loop {
+            let (node, remaining) = stack.last_mut().expect("the root is only popped at the end");
+            if *remaining > 0 {
+                *remaining -= 1;
+                let mut child_key = [0];
+                r.read_exact(&mut child_key)?;
+                if node.children.iter().any(|prev| prev.key == Some(child_key[0])) {
+                    return Err(invalid("duplicate children"));
+                }
+                stack.push(Trie::read_node(&mut r, Some(child_key[0]))?);
+                continue;
+            }
+            let (node, _) = stack.pop().expect("just looked at it");
+            match stack.last_mut() {
+                Some((parent, _)) => {
+                    if node.is_dead() {
+                        return Err(invalid("node leads to no value"));
+                    }
+                    parent.children.push(node);
+                },
+                None => return Ok(node),
+            }
+        }
+    }
+
+    /// Write one node's flags, value and child count, but not its children
+    fn write_node<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self.data {
+            Some(ref value) => {
+                w.write_all(&[TERMINAL])?;
+                write_bytes(w, value)?;
+            },
+            None => w.write_all(&[0])?,
+        }
+        w.write_all(&(self.children.len() as u16).to_le_bytes())
+    }
+
+    /// Read one node's flags, value and child count, returning the node (with no children yet)
+    /// and the count
+    fn read_node<R: Read>(r: &mut R, key: Option<u8>) -> io::Result<(Trie<u8, Vec<u8>>, usize)> {
+        let mut flags = [0];
+        r.read_exact(&mut flags)?;
+        let data = match flags[0] {
+            0 => None,
+            TERMINAL => Some(read_bytes(r)?.ok_or(io::ErrorKind::UnexpectedEof)?),
+            _ => return Err(invalid("unknown node flags")),
+        };
+        let mut count = [0; 2];
+        r.read_exact(&mut count)?;
+        let count = u16::from_le_bytes(count) as usize;
+        if count > 256 {
+            return Err(invalid("more children than there are bytes"));
+        }
+        let node = Trie {
+            children: Vec::with_capacity(count),
+            key,
+            data,
+        };
+        Ok((node, count))
+    }
+}
+
+impl TriePatch<u8, Vec<u8>> {
+
+    /// Write the patch, so it can be saved or sent elsewhere and read back with
+    /// [TriePatch::read_from]
+    ///
+    /// Each of `added`, `removed` and `changed` is written in turn: the number of items as a
+    /// little-endian `u32`, then each item's key (and value, except for removals) as
+    /// length-prefixed records, the same as [Trie::dump_to_writer]'s.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write_count(&mut w, self.added.len())?;
+        for (key, value) in &self.added {
+            write_bytes(&mut w, key)?;
+            write_bytes(&mut w, value)?;
+        }
+        write_count(&mut w, self.removed.len())?;
+        for key in &self.removed {
+            write_bytes(&mut w, key)?;
+        }
+        write_count(&mut w, self.changed.len())?;
+        for (key, value) in &self.changed {
+            write_bytes(&mut w, key)?;
+            write_bytes(&mut w, value)?;
+        }
+        Ok(())
+    }
+
+    /// Read a patch written by [TriePatch::write_to]
+    ///
+    /// Fails with `UnexpectedEof` if the stream ends before the patch does.
+    pub fn read_from<R: Read>(mut r: R) -> io::Result<TriePatch<u8, Vec<u8>>> {
+        let mut patch = TriePatch {
+            added: vec![],
+            removed: vec![],
+            changed: vec![],
+        };
+        // the counts come from the stream too, so nothing is reserved from them up front
+        for _ in 0..read_count(&mut r)? {
+            patch.added.push((read_record(&mut r)?, read_record(&mut r)?));
+        }
+        for _ in 0..read_count(&mut r)? {
+            patch.removed.push(read_record(&mut r)?);
+        }
+        for _ in 0..read_count(&mut r)? {
+            patch.changed.push((read_record(&mut r)?, read_record(&mut r)?));
+        }
+        Ok(patch)
+    }
+}
+
+/// Node flag for [Trie::write_tree] marking a node that holds a value
+const TERMINAL: u8 = 1;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_bytes<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+    if bytes.len() > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "key or value longer than u32::MAX"));
+    }
+    w.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+fn write_count<W: Write>(w: &mut W, count: usize) -> io::Result<()> {
+    if count > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "more than u32::MAX items"));
+    }
+    w.write_all(&(count as u32).to_le_bytes())
+}
+
+fn read_count<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut count = [0; 4];
+    r.read_exact(&mut count)?;
+    Ok(u32::from_le_bytes(count))
+}
+
+/// Read one length-prefixed run of bytes which must be there
+fn read_record<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    read_bytes(r)?.ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Read one length-prefixed run of bytes, or `None` if the stream ends cleanly first
+fn read_bytes<R: Read>(r: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match r.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    // the length comes from the stream, so don't trust it with an allocation up front: the
+    // buffer only grows as the bytes actually arrive
+    let len = u32::from_le_bytes(len) as u64;
+    let mut bytes = vec![];
+    r.by_ref().take(len).read_to_end(&mut bytes)?;
+    if (bytes.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use {Trie, TriePatch};
+
+    fn sample() -> Trie<u8, Vec<u8>> {
+        let mut t = Trie::new_empty();
+        for (key, value) in [("", "root"), ("a", ""), ("ab", "two"), ("xyz", "three")].iter() {
+            assert_eq!(t.insert_bytes(key.as_bytes(), value.as_bytes().to_vec()), Ok(()));
+        }
+        t.insert_bytes(&[0, 255, 10], vec![1; 1000]).unwrap();
+        t
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let t = sample();
+        let mut buf = vec![];
+        assert_eq!(t.dump_to_writer(&mut buf).unwrap(), 5);
+
+        let mut loaded = Trie::new_empty();
+        assert_eq!(loaded.load_from_reader(&buf[..]).unwrap(), 5);
+        assert_eq!(loaded, t);
+
+        // loading into a non-empty Trie replaces existing values
+        let mut existing = Trie::new_empty();
+        existing.insert_bytes(b"ab", b"old".to_vec()).unwrap();
+        existing.insert_bytes(b"keep", b"me".to_vec()).unwrap();
+        assert_eq!(existing.load_from_reader(&buf[..]).unwrap(), 5);
+        assert_eq!(existing.len(), 6);
+        assert_eq!(existing.get_bytes(b"ab"), Some(&b"two".to_vec()));
+
+        let mut empty = Trie::new_empty();
+        assert_eq!(empty.load_from_reader(&[][..]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_record_truncated() {
+        let mut buf = vec![];
+        sample().dump_to_writer(&mut buf).unwrap();
+
+        for cut in [1, 4, 5, buf.len() - 1].iter() {
+            let mut loaded = Trie::new_empty();
+            let err = loaded.load_from_reader(&buf[..buf.len() - cut]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn test_record_huge_length() {
+        // a length prefix of u32::MAX with only a few bytes behind it
+        let buf = [255, 255, 255, 255, b'a', b'b'];
+        let mut loaded = Trie::new_empty();
+        let err = loaded.load_from_reader(&buf[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(loaded.is_empty());
+
+        let err = Trie::read_tree(&[1, 255, 255, 255, 255, 0][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_patch_round_trip() {
+        let old = sample();
+        let mut new = sample();
+        new.remove(b"ab".iter().cloned());
+        new.insert_bytes(b"new", b"value".to_vec()).unwrap();
+        *new.get_mut(b"xyz".iter().cloned()).unwrap() = b"changed".to_vec();
+
+        let patch = old.diff(&new).to_patch();
+        let mut buf = vec![];
+        patch.write_to(&mut buf).unwrap();
+        let read = TriePatch::read_from(&buf[..]).unwrap();
+        assert_eq!(read, patch);
+
+        let mut patched = old.clone();
+        patched.apply_patch(&read);
+        assert_eq!(patched, new);
+
+        for cut in [1, 4, buf.len() - 1].iter() {
+            let err = TriePatch::read_from(&buf[..buf.len() - cut]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        // a huge count with nothing behind it
+        let err = TriePatch::read_from(&[255, 255, 255, 255][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_tree_very_long_key() {
+        let key: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut t = sample();
+        t.insert_bytes(&key, b"long".to_vec()).unwrap();
+        t.insert_bytes(&key[..10], b"short".to_vec()).unwrap();
+
+        let mut buf = vec![];
+        t.write_tree(&mut buf).unwrap();
+        let read = Trie::read_tree(&buf[..]).unwrap();
+        assert_eq!(read.get_bytes(&key), Some(&b"long".to_vec()));
+        assert_eq!(read.len(), t.len());
+        assert_eq!(read, t);
+    }
+
+    #[test]
+    fn test_tree_round_trip() {
+        let t = sample();
+        let mut buf = vec![];
+        t.write_tree(&mut buf).unwrap();
+        let loaded = Trie::read_tree(&buf[..]).unwrap();
+        assert_eq!(loaded, t);
+        assert_eq!(loaded.node_count(), t.node_count());
+
+        // with long shared prefixes, the structure beats writing every key out whole
+        let mut urls = Trie::new_empty();
+        for i in 0..100 {
+            let key = format!("https://example.com/items/{}", i);
+            urls.insert_bytes(key.as_bytes(), vec![i as u8]).unwrap();
+        }
+        let (mut tree, mut records) = (vec![], vec![]);
+        urls.write_tree(&mut tree).unwrap();
+        urls.dump_to_writer(&mut records).unwrap();
+        assert!(tree.len() < records.len());
+        assert_eq!(Trie::read_tree(&tree[..]).unwrap(), urls);
+
+        let mut buf = vec![];
+        Trie::new_empty().write_tree(&mut buf).unwrap();
+        assert_eq!(buf, vec![0, 0, 0]);
+        assert!(Trie::read_tree(&buf[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tree_malformed() {
+        let mut buf = vec![];
+        sample().write_tree(&mut buf).unwrap();
+        let err = Trie::read_tree(&buf[..buf.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let bad: [&[u8]; 3] = [
+            // unknown flags
+            &[2, 0, 0],
+            // two children under 'a'
+            &[0, 2, 0, b'a', 1, 0, 0, 0, 0, 0, 0, b'a', 1, 0, 0, 0, 0, 0, 0],
+            // a child with no value and no children
+            &[0, 1, 0, b'a', 0, 0, 0],
+        ];
+        for bytes in bad.iter() {
+            assert_eq!(Trie::read_tree(*bytes).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use {ErrType, Trie};
+use iter::Iter;
+
+/// A handle on the part of a [Trie] below a prefix, made with [Trie::prefix_mut]
+///
+/// Keys given to and returned from the view are relative to the prefix, which is found once
+/// rather than on every call.
+pub struct PrefixView<'a, K: 'a, D: 'a> {
+    node: &'a mut Trie<K, D>,
+}
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Get a view of everything stored under `prefix`
+    ///
+    /// Returns `None` if no stored key starts with the prefix (or is the prefix itself).
+    pub fn prefix_mut<F: IntoIterator<Item=K>>(&mut self, prefix: F) -> Option<PrefixView<'_, K, D>> {
+        match self.find_node_mut(prefix.into_iter()) {
+            Some(node) if !node.is_dead() => Some(PrefixView { node }),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, K: PartialEq + Copy, D> PrefixView<'a, K, D> {
+
+    /// Insert a value at `key` below the prefix
+    ///
+    /// Inserting a key that is already present is illegal, as for [Trie::insert].
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        self.node.insert(key, data)
+    }
+
+    /// Get the value stored at exactly `key` below the prefix
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        self.node.get(key)
+    }
+
+    /// Get a mutable reference to the value stored at exactly `key` below the prefix
+    pub fn get_mut<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&mut D> {
+        self.node.get_mut(key)
+    }
+
+    /// Check whether a value is stored at exactly `key` below the prefix
+    pub fn contains_key<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        self.node.contains_key(key)
+    }
+
+    /// Iterate over every key and value below the prefix, with the prefix left off the keys
+    pub fn iter(&self) -> Iter<'_, K, D> {
+        Iter::new(Some(&*self.node), vec![])
+    }
+
+    /// The number of values stored below the prefix
+    pub fn len(&self) -> usize {
+        self.node.len()
+    }
+
+    /// Whether nothing is stored below the prefix; at least one value was when the view was made
+    pub fn is_empty(&self) -> bool {
+        self.node.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use {Trie, TrieError};
+
+    #[test]
+    fn test_prefix_mut() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_str("/usr/bin", 0), Ok(()));
+        assert_eq!(t.insert_str("/var", 1), Ok(()));
+
+        {
+            let mut usr = t.prefix_mut("/usr/".chars()).unwrap();
+            assert_eq!(usr.insert("lib".chars(), 2), Ok(()));
+            assert_eq!(usr.insert("local".chars(), 3), Ok(()));
+            assert_eq!(usr.insert("bin".chars(), 4), Err(TrieError::KeyAlreadyPresent));
+            *usr.get_mut("local".chars()).unwrap() += 10;
+            assert_eq!(usr.get("bin".chars()), Some(&0));
+            assert!(!usr.contains_key("/usr/bin".chars()));
+            assert_eq!(usr.len(), 3);
+            let keys: Vec<String> = usr.iter().map(|(k, _)| k.into_iter().collect()).collect();
+            assert_eq!(keys, vec!["bin", "lib", "local"]);
+        }
+
+        assert_eq!(t.get("/usr/lib".chars()), Some(&2));
+        assert_eq!(t.get("/usr/local".chars()), Some(&13));
+        assert_eq!(t.get("lib".chars()), None);
+        assert_eq!(t.len(), 4);
+    }
+
+    #[test]
+    fn test_prefix_mut_missing_or_empty() {
+        let mut t: Trie<char, i32> = Trie::new_empty();
+        assert!(t.prefix_mut("".chars()).is_none());
+
+        assert_eq!(t.insert_str("ab", 1), Ok(()));
+        assert!(t.prefix_mut("b".chars()).is_none());
+        assert!(t.prefix_mut("abc".chars()).is_none());
+        // the prefix can be a whole key
+        let mut ab = t.prefix_mut("ab".chars()).unwrap();
+        assert_eq!(ab.get("".chars()), Some(&1));
+        assert_eq!(ab.insert("".chars(), 2), Err(TrieError::KeyAlreadyPresent));
+
+        let mut all = t.prefix_mut("".chars()).unwrap();
+        assert_eq!(all.insert("b".chars(), 3), Ok(()));
+        assert_eq!(t.to_sorted_vec(), vec![(vec!['a', 'b'], &1), (vec!['b'], &3)]);
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use Trie;
+
+/// A Patricia (radix) compressed form of a [Trie]
+///
+/// Runs of nodes which have a single child and no value of their own are collapsed into one
+/// node, whose edge is labelled with the whole run of key elements. Built with
+/// [Trie::compress]; queries answer exactly as they would on the source Trie.
+#[derive(Clone, Debug)]
+pub struct RadixTrie<K, D> {
+    children: Vec<RadixTrie<K, D>>,
+    label: Vec<K>,
+    data: Option<D>,
+}
+
+impl<K: PartialEq + Copy, D> RadixTrie<K, D> {
+
+    pub(crate) fn from_trie(mut trie: Trie<K, D>) -> RadixTrie<K, D> {
+        // the root never has a key, so it only needs its children compressed
+        RadixTrie {
+            children: mem::take(&mut trie.children).into_iter().map(RadixTrie::compress_node).collect(),
+            label: vec![],
+            data: trie.data.take(),
+        }
+    }
+
+    fn compress_node(mut node: Trie<K, D>) -> RadixTrie<K, D> {
+        let mut label: Vec<K> = node.key.into_iter().collect();
+        while node.data.is_none() && node.children.len() == 1 {
+            node = node.children.pop().expect("node has exactly one child");
+            label.extend(node.key);
+        }
+        RadixTrie {
+            children: mem::take(&mut node.children).into_iter().map(RadixTrie::compress_node).collect(),
+            label,
+            data: node.data.take(),
+        }
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        self.descend(key.into_iter()).1
+    }
+
+    /// Search for the longest match in the RadixTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        self.descend(key.into_iter()).0
+    }
+
+    /// Walk down along the key, returning the longest-prefix match and the exact match
+    fn descend<F: Iterator<Item=K>>(&self, mut key_elems: F) -> (Option<&D>, Option<&D>) {
+        let mut node = self;
+        let mut longest = self.data.as_ref();
+        loop {
+            let first = match key_elems.next() {
+                Some(key_elem) => key_elem,
+                None => return (longest, node.data.as_ref()),
+            };
+            let child = match node.children.iter().find(|child| child.label.first() == Some(&first)) {
+                Some(child) => child,
+                None => return (longest, None),
+            };
+            // the rest of the edge has to match too, or the key ends partway along it
+            for &label_elem in &child.label[1..] {
+                if key_elems.next() != Some(label_elem) {
+                    return (longest, None);
+                }
+            }
+            node = child;
+            if let Some(ref data) = node.data {
+                longest = Some(data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+    use super::RadixTrie;
+
+    fn count_nodes<K, D>(t: &RadixTrie<K, D>) -> usize {
+        1 + t.children.iter().map(count_nodes).sum::<usize>()
+    }
+
+    fn build() -> Trie<char, i32> {
+        let mut t = Trie::new_empty();
+        let words = ["romane", "romanus", "romulus", "rubens", "ruber", "rubicon", "rubicundus", "rom"];
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i as i32), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_compress_matches_trie() {
+        let t = build();
+        let r = build().compress();
+
+        let queries = ["", "r", "ro", "rom", "roma", "romane", "romanes", "romanus", "romulusx",
+                       "rub", "rube", "rubens", "ruber", "rubicon", "rubiconx", "rubicundus",
+                       "rubicund", "x", "romu"];
+        for query in queries.iter() {
+            assert_eq!(r.get(query.chars()), t.get(query.chars()), "get {}", query);
+            assert_eq!(r.search(query.chars()), t.search(query.chars()), "search {}", query);
+        }
+    }
+
+    #[test]
+    fn test_compress_collapses_chains() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("abcdef".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abcxyz".chars(), 2), Ok(()));
+
+        let r = t.compress();
+        // root, "abc", "def" and "xyz"
+        assert_eq!(count_nodes(&r), 4);
+        assert_eq!(r.children[0].label, vec!['a', 'b', 'c']);
+        assert_eq!(r.get("abcdef".chars()), Some(&1));
+        assert_eq!(r.get("abc".chars()), None);
+        assert_eq!(r.search("abcxyzzy".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_compress_keeps_terminal_nodes() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert("".chars(), 0), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 1), Ok(()));
+        assert_eq!(t.insert("abcd".chars(), 2), Ok(()));
+
+        let r = t.compress();
+        assert_eq!(count_nodes(&r), 3);
+        assert_eq!(r.get("".chars()), Some(&0));
+        assert_eq!(r.search("abx".chars()), Some(&1));
+        assert_eq!(r.search("abc".chars()), Some(&1));
+        assert_eq!(r.search("x".chars()), Some(&0));
+        assert_eq!(r.get("abcd".chars()), Some(&2));
+    }
+}
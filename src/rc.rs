@@ -0,0 +1,268 @@
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::mem;
+
+use {ErrType, TrieError};
+
+/// A persistent Trie whose snapshots share structure
+///
+/// Every node sits behind an `Rc`, so [RcTrie::snapshot] (or `clone`) is O(1). Changes are
+/// copy-on-write: only the nodes along the modified key's path are copied, and only when a
+/// snapshot still shares them, while every other subtree stays shared.
+pub struct RcTrie<K, D> {
+    root: Rc<RcNode<K, D>>,
+}
+
+#[derive(Clone)]
+struct RcNode<K, D> {
+    children: Vec<Rc<RcNode<K, D>>>,
+    key: Option<K>,
+    data: Option<D>,
+}
+
+impl<K: PartialEq + Copy, D> RcTrie<K, D> {
+
+    /// Construct a new, empty RcTrie
+    pub fn new_empty() -> RcTrie<K, D> {
+        RcTrie {
+            root: Rc::new(RcNode::new(None)),
+        }
+    }
+
+    /// Take a snapshot of the RcTrie as it is now, sharing all of its nodes
+    ///
+    /// Later changes to either copy don't show up in the other.
+    pub fn snapshot(&self) -> RcTrie<K, D> {
+        RcTrie {
+            root: Rc::clone(&self.root),
+        }
+    }
+
+    /// Search for the longest match in the RcTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &*self.root;
+        let mut longest = node.data.as_ref();
+        for key_elem in key {
+            node = match node.child_position(key_elem) {
+                Some(index) => &node.children[index],
+                None => break,
+            };
+            longest = node.data.as_ref().or(longest);
+        }
+        longest
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &*self.root;
+        for key_elem in key {
+            node = &node.children[node.child_position(key_elem)?];
+        }
+        node.data.as_ref()
+    }
+
+    /// Check whether a value is stored at exactly this key
+    pub fn contains_key<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Count the values stored in the RcTrie
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    /// Check whether the RcTrie holds no values at all
+    pub fn is_empty(&self) -> bool {
+        self.root.data.is_none() && self.root.children.is_empty()
+    }
+}
+
+impl<K: PartialEq + Copy, D: Clone> RcTrie<K, D> {
+
+    /// Insert a new value into the RcTrie
+    ///
+    /// Inserting a key that is already present is illegal, and copies nothing.
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        let key: Vec<K> = key.into_iter().collect();
+        if self.contains_key(key.iter().cloned()) {
+            return Err(TrieError::KeyAlreadyPresent);
+        }
+        let mut node = Rc::make_mut(&mut self.root);
+        for key_elem in key {
+            let index = match node.child_position(key_elem) {
+                Some(index) => index,
+                None => {
+                    node.children.push(Rc::new(RcNode::new(Some(key_elem))));
+                    node.children.len() - 1
+                }
+            };
+            node = Rc::make_mut(&mut node.children[index]);
+        }
+        node.data = Some(data);
+        Ok(())
+    }
+
+    /// Remove a key from the RcTrie, returning its value
+    ///
+    /// Nodes left leading nowhere are pruned. Nothing is copied if the key isn't present.
+    pub fn remove<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<D> {
+        // the index of each child along the key, and the deepest node on the path that still
+        // leads somewhere once the value is gone, as in Trie::remove_iter
+        let mut indices = vec![];
+        let mut keep = 0;
+        let mut node = &*self.root;
+        for key_elem in key {
+            if node.data.is_some() || node.children.len() > 1 {
+                keep = indices.len();
+            }
+            let index = node.child_position(key_elem)?;
+            indices.push(index);
+            node = &node.children[index];
+        }
+        node.data.as_ref()?;
+        if !node.children.is_empty() {
+            keep = indices.len();
+        }
+
+        let mut node = Rc::make_mut(&mut self.root);
+        for &index in &indices[..keep] {
+            node = Rc::make_mut(&mut node.children[index]);
+        }
+        if keep == indices.len() {
+            return node.data.take();
+        }
+        // everything below `keep` is a chain ending at the value, so cut it off whole; the
+        // value is only copied if a snapshot still shares it
+        let mut chain = node.children.remove(indices[keep]);
+        for &index in &indices[keep + 1..] {
+            chain = Rc::clone(&chain.children[index]);
+        }
+        match Rc::try_unwrap(chain) {
+            Ok(mut node) => node.data.take(),
+            Err(shared) => shared.data.clone(),
+        }
+    }
+}
+
+impl<K, D> Clone for RcTrie<K, D> {
+
+    /// The same as [RcTrie::snapshot]
+    fn clone(&self) -> RcTrie<K, D> {
+        RcTrie {
+            root: Rc::clone(&self.root),
+        }
+    }
+}
+
+impl<K: PartialEq + Copy, D> Default for RcTrie<K, D> {
+
+    fn default() -> RcTrie<K, D> {
+        RcTrie::new_empty()
+    }
+}
+
+impl<K: PartialEq + Copy, D> RcNode<K, D> {
+
+    fn new(key: Option<K>) -> RcNode<K, D> {
+        RcNode {
+            children: vec![],
+            key,
+            data: None,
+        }
+    }
+
+    fn child_position(&self, key_elem: K) -> Option<usize> {
+        self.children.iter().position(|child| child.key == Some(key_elem))
+    }
+
+    fn len(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.data.is_some() {
+                count += 1;
+            }
+            stack.extend(node.children.iter().map(|child| &**child));
+        }
+        count
+    }
+}
+
+impl<K, D> Drop for RcNode<K, D> {
+
+    /// Free the nodes no snapshot still shares a node at a time, since the default recursive
+    /// drop would overflow the stack on a very long key
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(child) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(child) {
+                stack.append(&mut node.children);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+
+    use super::RcTrie;
+
+    fn sample() -> RcTrie<char, usize> {
+        let mut t = RcTrie::new_empty();
+        for (i, word) in ["apple", "apply", "banana", "band"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        t
+    }
+
+    #[test]
+    fn test_rc_trie() {
+        let mut t = sample();
+        assert_eq!(t.len(), 4);
+        assert_eq!(t.get("apply".chars()), Some(&1));
+        assert_eq!(t.get("app".chars()), None);
+        assert_eq!(t.search("bandana".chars()), Some(&3));
+        assert!(t.insert("band".chars(), 9).is_err());
+
+        assert_eq!(t.remove("banana".chars()), Some(2));
+        assert_eq!(t.remove("banana".chars()), None);
+        assert_eq!(t.get("band".chars()), Some(&3));
+        assert_eq!(t.remove("band".chars()), Some(3));
+        assert_eq!(t.root.children.len(), 1);
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut live = sample();
+        let snapshot = live.snapshot();
+        assert!(Rc::ptr_eq(&live.root, &snapshot.root));
+
+        assert_eq!(live.insert("apricot".chars(), 10), Ok(()));
+        assert_eq!(live.remove("band".chars()), Some(3));
+
+        assert_eq!(snapshot.len(), 4);
+        assert_eq!(snapshot.get("band".chars()), Some(&3));
+        assert_eq!(snapshot.get("apricot".chars()), None);
+        assert_eq!(live.len(), 4);
+        assert_eq!(live.get("apricot".chars()), Some(&10));
+        assert_eq!(live.get("band".chars()), None);
+
+        // "app" wasn't on either changed path, so it's still shared
+        let app = |t: &RcTrie<char, usize>| Rc::clone(&t.root.children[0].children[0].children[0]);
+        assert!(Rc::ptr_eq(&app(&live), &app(&snapshot)));
+        assert!(!Rc::ptr_eq(&live.root.children[0], &snapshot.root.children[0]));
+        // "bana" was untouched by removing "band"
+        let bana = |t: &RcTrie<char, usize>| {
+            Rc::clone(&t.root.children[1].children[0].children[0].children[0])
+        };
+        assert!(Rc::ptr_eq(&bana(&live), &bana(&snapshot)));
+
+        // a failed change copies nothing
+        let before = live.snapshot();
+        assert!(live.insert("apple".chars(), 0).is_err());
+        assert_eq!(live.remove("cherry".chars()), None);
+        assert!(Rc::ptr_eq(&live.root, &before.root));
+    }
+}
@@ -0,0 +1,75 @@
+//! IPv4 longest-prefix-match routing on a `Trie<bool, D>`
+//!
+//! Network prefixes are stored as their leading address bits, most significant first, so the
+//! most specific route covering an address is just the longest match for its 32 bits.
+
+use Trie;
+
+impl<D> Trie<bool, D> {
+
+    /// Store a route for the network `prefix/prefix_len`, returning any route previously
+    /// stored for exactly that network
+    ///
+    /// Bits of `prefix` past `prefix_len` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is greater than 32.
+    pub fn insert_cidr(&mut self, prefix: u32, prefix_len: u8, route: D) -> Option<D> {
+        assert!(prefix_len <= 32, "an IPv4 prefix is at most 32 bits long");
+        self.insert_or_replace(address_bits(prefix, prefix_len), route)
+    }
+
+    /// Find the most specific route covering `addr`
+    pub fn lookup(&self, addr: u32) -> Option<&D> {
+        self.search(address_bits(addr, 32))
+    }
+}
+
+/// The first `len` bits of an address, most significant first
+fn address_bits(addr: u32, len: u8) -> impl Iterator<Item=bool> {
+    (0..len).map(move |i| addr & (0x8000_0000 >> i) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+
+    fn ip(a: u8, b: u8, c: u8, d: u8) -> u32 {
+        u32::from_be_bytes([a, b, c, d])
+    }
+
+    #[test]
+    fn test_most_specific_route_wins() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_cidr(ip(10, 0, 0, 0), 8, "ten"), None);
+        assert_eq!(t.insert_cidr(ip(10, 1, 0, 0), 16, "ten-one"), None);
+        assert_eq!(t.insert_cidr(ip(10, 1, 2, 3), 32, "host"), None);
+
+        assert_eq!(t.lookup(ip(10, 1, 5, 5)), Some(&"ten-one"));
+        assert_eq!(t.lookup(ip(10, 2, 5, 5)), Some(&"ten"));
+        assert_eq!(t.lookup(ip(10, 1, 2, 3)), Some(&"host"));
+        assert_eq!(t.lookup(ip(10, 1, 2, 4)), Some(&"ten-one"));
+        assert_eq!(t.lookup(ip(192, 168, 0, 1)), None);
+    }
+
+    #[test]
+    fn test_default_route_and_replacement() {
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert_cidr(0, 0, "default"), None);
+        assert_eq!(t.insert_cidr(ip(192, 168, 0, 0), 16, "lan"), None);
+        // host bits past the prefix length don't matter
+        assert_eq!(t.insert_cidr(ip(192, 168, 9, 9), 16, "new lan"), Some("lan"));
+
+        assert_eq!(t.lookup(ip(192, 168, 1, 1)), Some(&"new lan"));
+        assert_eq!(t.lookup(ip(8, 8, 8, 8)), Some(&"default"));
+        assert_eq!(t.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 32 bits")]
+    fn test_prefix_too_long() {
+        let mut t = Trie::new_empty();
+        t.insert_cidr(0, 33, ());
+    }
+}
@@ -0,0 +1,208 @@
+use iter::Iter;
+use {ErrType, Trie, TrieError};
+
+/// A [Trie] over `Ord` key elements which keeps every node's children sorted
+///
+/// Each level of a descent is a binary search rather than the linear scan a plain Trie makes,
+/// which pays off for wide nodes, and iteration visits keys in lexicographic order.
+#[derive(Clone)]
+pub struct SortedTrie<K, D> {
+    trie: Trie<K, D>,
+}
+
+impl<K: Ord + Copy, D> SortedTrie<K, D> {
+
+    /// Construct a new, empty SortedTrie
+    pub fn new_empty() -> SortedTrie<K, D> {
+        SortedTrie {
+            trie: Trie::new_empty(),
+        }
+    }
+
+    /// Sort the children of every node in `trie`
+    pub fn from_trie(mut trie: Trie<K, D>) -> SortedTrie<K, D> {
+        let mut stack = vec![&mut trie];
+        while let Some(node) = stack.pop() {
+            node.children.sort_unstable_by_key(|child| child.key);
+            stack.extend(node.children.iter_mut());
+        }
+        SortedTrie { trie }
+    }
+
+    /// Insert a new value into the SortedTrie through an iterator.
+    ///
+    /// Inserting a key which matches one already present is illegal.
+    pub fn insert_iter<F: Iterator<Item=K>>(&mut self, key_elems: F, data: D) -> ErrType {
+        let mut node = &mut self.trie;
+        for key_elem in key_elems {
+            let index = match child_index(node, key_elem) {
+                Ok(index) => index,
+                Err(index) => {
+                    node.children.insert(index, Trie::new_node(key_elem));
+                    index
+                }
+            };
+            node = &mut node.children[index];
+        }
+        if node.data.is_some() {
+            return Err(TrieError::KeyAlreadyPresent);
+        }
+        node.data = Some(data);
+        Ok(())
+    }
+
+    /// Insert a new value into the SortedTrie
+    ///
+    /// Syntactic sugar for [self.insert_iter]
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        self.insert_iter(key.into_iter(), data)
+    }
+
+    /// Search for the longest match in the SortedTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &self.trie;
+        let mut longest = node.data.as_ref();
+        for key_elem in key {
+            node = match child_index(node, key_elem) {
+                Ok(index) => &node.children[index],
+                Err(_) => break,
+            };
+            if let Some(ref data) = node.data {
+                longest = Some(data);
+            }
+        }
+        longest
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut node = &self.trie;
+        for key_elem in key {
+            node = &node.children[child_index(node, key_elem).ok()?];
+        }
+        node.data.as_ref()
+    }
+
+    /// Get a mutable reference to the value stored at exactly this key
+    pub fn get_mut<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<&mut D> {
+        let mut node = &mut self.trie;
+        for key_elem in key {
+            let index = child_index(node, key_elem).ok()?;
+            node = &mut node.children[index];
+        }
+        node.data.as_mut()
+    }
+
+    /// Whether a value is stored at exactly this key
+    pub fn contains_key<F: IntoIterator<Item=K>>(&self, key: F) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove the value stored at exactly this key, returning it
+    ///
+    /// Removal only ever drops children, so the rest stay sorted.
+    pub fn remove<F: IntoIterator<Item=K>>(&mut self, key: F) -> Option<D> {
+        self.trie.remove(key)
+    }
+
+    /// The number of values stored
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// Iterate over every key and value, in lexicographic order
+    pub fn iter(&self) -> Iter<'_, K, D> {
+        self.trie.iter()
+    }
+
+    /// The sorted Trie, for every query that doesn't need a binary search
+    pub fn as_trie(&self) -> &Trie<K, D> {
+        &self.trie
+    }
+
+    /// Take the sorted Trie out
+    pub fn into_trie(self) -> Trie<K, D> {
+        self.trie
+    }
+}
+
+impl<K, D> Default for SortedTrie<K, D> {
+
+    /// Construct a new, empty SortedTrie; the same as [SortedTrie::new_empty]
+    fn default() -> SortedTrie<K, D> {
+        SortedTrie {
+            trie: Trie::default(),
+        }
+    }
+}
+
+/// Binary search the (sorted) children of `node` for the given key element
+///
+/// Like `slice::binary_search`, an `Err` holds the index where that child would be inserted.
+fn child_index<K: Ord + Copy, D>(node: &Trie<K, D>, key_elem: K) -> Result<usize, usize> {
+    node.children.binary_search_by_key(&Some(key_elem), |child| child.key)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use {Trie, TrieError};
+    use super::SortedTrie;
+
+    #[test]
+    fn test_children_are_sorted() {
+        let mut t = SortedTrie::new_empty();
+        for (i, word) in ["pear", "apple", "peach", "fig", "banana", "pea"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        assert_eq!(t.insert("fig".chars(), 9), Err(TrieError::KeyAlreadyPresent));
+
+        let root_keys: Vec<Option<char>> = t.as_trie().children.iter()
+            .map(|child| child.key)
+            .collect();
+        assert_eq!(root_keys, vec![Some('a'), Some('b'), Some('f'), Some('p')]);
+        let entries: Vec<(String, usize)> = t.iter()
+            .map(|(k, &v)| (k.into_iter().collect(), v))
+            .collect();
+        assert_eq!(entries, vec![
+            ("apple".to_string(), 1), ("banana".to_string(), 4), ("fig".to_string(), 3),
+            ("pea".to_string(), 5), ("peach".to_string(), 2), ("pear".to_string(), 0),
+        ]);
+        assert_eq!(t.get("peach".chars()), Some(&2));
+        assert_eq!(t.search("pearl".chars()), Some(&0));
+        assert_eq!(t.get("pe".chars()), None);
+        *t.get_mut("fig".chars()).unwrap() += 10;
+        assert_eq!(t.get("fig".chars()), Some(&13));
+
+        assert_eq!(t.remove("pea".chars()), Some(5));
+        assert!(!t.contains_key("pea".chars()));
+        assert!(t.contains_key("peach".chars()));
+        assert_eq!(t.len(), 5);
+    }
+
+    #[test]
+    fn test_from_trie() {
+        let words = ["pear", "apple", "peach", "fig", "banana", "pea"];
+        let mut plain = Trie::new_empty();
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(plain.insert(word.chars(), i), Ok(()));
+        }
+        let mut expected: Vec<(Vec<char>, usize)> = plain.iter().map(|(k, &v)| (k, v)).collect();
+        expected.sort();
+
+        let sorted = SortedTrie::from_trie(plain.clone());
+        let entries: Vec<(Vec<char>, usize)> = sorted.iter().map(|(k, &v)| (k, v)).collect();
+        assert_eq!(entries, expected);
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(sorted.get(word.chars()), Some(&i));
+        }
+        assert_eq!(sorted.into_trie(), plain);
+        assert!(SortedTrie::<char, usize>::default().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::vec::Vec;
+
+use Trie;
+
+/// A summary of a Trie's shape, from [Trie::stats]
+///
+/// As with [Trie::node_count] and [Trie::count_leaves], the root isn't counted as a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrieStats {
+    /// Stored values; the same as [Trie::len]
+    pub entries: usize,
+    /// Nodes below the root; the same as [Trie::node_count]
+    pub nodes: usize,
+    /// Nodes with no children; the same as [Trie::count_leaves]
+    pub leaves: usize,
+    /// The deepest node's distance from the root; the same as [Trie::height]
+    pub height: usize,
+    /// The most children any one node (root included) has
+    pub max_fanout: usize,
+    /// The mean number of children of the nodes which have any (root included), or 0 if none
+    /// do
+    pub avg_fanout: f64,
+}
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Gather [TrieStats] about the Trie in a single walk
+    ///
+    /// Much cheaper than calling [self.len], [self.node_count] and [self.height] one by one on
+    /// a large Trie.
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats {
+            entries: 0,
+            nodes: 0,
+            leaves: 0,
+            height: 0,
+            max_fanout: 0,
+            avg_fanout: 0.0,
+        };
+        let mut interior = 0;
+        let mut stack: Vec<(&Trie<K, D>, usize)> = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if node.data.is_some() {
+                stats.entries += 1;
+            }
+            if depth > 0 {
+                stats.nodes += 1;
+                if node.children.is_empty() {
+                    stats.leaves += 1;
+                }
+            }
+            if !node.children.is_empty() {
+                interior += 1;
+            }
+            stats.height = stats.height.max(depth);
+            stats.max_fanout = stats.max_fanout.max(node.children.len());
+            stack.extend(node.children.iter().map(|child| (child, depth + 1)));
+        }
+        if interior > 0 {
+            // every node but the root is somebody's child
+            stats.avg_fanout = stats.nodes as f64 / interior as f64;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Trie;
+    use super::TrieStats;
+
+    #[test]
+    fn test_stats() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "card", "care", "cat", "do"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        // root -> c, d; c -> a; a -> r, t; r -> d, e; d -> o: five nodes with children
+        assert_eq!(t.stats(), TrieStats {
+            entries: 5,
+            nodes: 8,
+            leaves: 4,
+            height: 4,
+            max_fanout: 2,
+            avg_fanout: 8.0 / 5.0,
+        });
+        let stats = t.stats();
+        assert_eq!(stats.entries, t.len());
+        assert_eq!(stats.nodes, t.node_count());
+        assert_eq!(stats.leaves, t.count_leaves());
+        assert_eq!(stats.height, t.height());
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let mut t: Trie<char, usize> = Trie::new_empty();
+        let stats = t.stats();
+        assert_eq!((stats.entries, stats.nodes, stats.leaves, stats.height), (0, 0, 0, 0));
+        assert_eq!(stats.avg_fanout, 0.0);
+
+        assert_eq!(t.insert("".chars(), 0), Ok(()));
+        assert_eq!(t.stats().entries, 1);
+        assert_eq!(t.stats().nodes, 0);
+    }
+}
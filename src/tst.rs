@@ -0,0 +1,339 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use {ErrType, TrieError};
+
+/// A Trie stored as a ternary search tree
+///
+/// Each node holds one key element and three children: `lo` and `hi` lead to nodes with smaller
+/// and larger elements at the same position in the key, and `eq` leads on to the next position.
+/// So each level of a descent is a walk down a little binary search tree, with no `Vec` (or
+/// `HashMap`) per node, which saves memory for large, sparse alphabets. `insert`, `search` and
+/// `get` behave exactly as they do on [Trie](::Trie).
+pub struct TstTrie<K, D> {
+    root: Option<Box<TstNode<K, D>>>,
+    /// the value stored under the empty key, which has no node to live in
+    empty: Option<D>,
+}
+
+struct TstNode<K, D> {
+    key: K,
+    lo: Option<Box<TstNode<K, D>>>,
+    eq: Option<Box<TstNode<K, D>>>,
+    hi: Option<Box<TstNode<K, D>>>,
+    data: Option<D>,
+}
+
+impl<K: Ord + Copy, D> TstTrie<K, D> {
+
+    /// Construct a new, empty TstTrie
+    pub fn new_empty() -> TstTrie<K, D> {
+        TstTrie {
+            root: None,
+            empty: None,
+        }
+    }
+
+    /// Insert a new value into the TstTrie
+    ///
+    /// Inserting a key that is already present is illegal.
+    pub fn insert<F: IntoIterator<Item=K>>(&mut self, key: F, data: D) -> ErrType {
+        let mut key_elems = key.into_iter();
+        let mut key_elem = match key_elems.next() {
+            Some(key_elem) => key_elem,
+            None => return store(&mut self.empty, data),
+        };
+        let mut link = &mut self.root;
+        loop {
+            let node = link.get_or_insert_with(|| Box::new(TstNode::new(key_elem)));
+            link = match key_elem.cmp(&node.key) {
+                Ordering::Less => &mut node.lo,
+                Ordering::Greater => &mut node.hi,
+                Ordering::Equal => match key_elems.next() {
+                    Some(next) => {
+                        key_elem = next;
+                        &mut node.eq
+                    },
+                    None => return store(&mut node.data, data),
+                },
+            };
+        }
+    }
+
+    /// Search for the longest match in the TstTrie
+    pub fn search<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut longest = self.empty.as_ref();
+        let mut link = &self.root;
+        for key_elem in key {
+            match find(link, key_elem) {
+                Some(node) => {
+                    longest = node.data.as_ref().or(longest);
+                    link = &node.eq;
+                },
+                None => break,
+            }
+        }
+        longest
+    }
+
+    /// Look up the value stored at exactly this key
+    pub fn get<F: IntoIterator<Item=K>>(&self, key: F) -> Option<&D> {
+        let mut data = self.empty.as_ref();
+        let mut link = &self.root;
+        for key_elem in key {
+            let node = find(link, key_elem)?;
+            data = node.data.as_ref();
+            link = &node.eq;
+        }
+        data
+    }
+
+    /// Iterate over every key and value, in key order
+    pub fn iter(&self) -> TstIter<'_, K, D> {
+        TstIter::new(self)
+    }
+}
+
+impl<K: Ord + Copy, D> Default for TstTrie<K, D> {
+
+    fn default() -> TstTrie<K, D> {
+        TstTrie::new_empty()
+    }
+}
+
+impl<K: Clone, D: Clone> Clone for TstTrie<K, D> {
+
+    /// Copy the tree a node at a time, building each node once its children are copied, since
+    /// a derived (recursive) clone would overflow the stack on a very long key
+    fn clone(&self) -> TstTrie<K, D> {
+        // each link, with whether its node's children have been copied yet
+        let mut stack = vec![(&self.root, false)];
+        // copies of finished links, waiting to be collected by their parent
+        let mut copies: Vec<Option<Box<TstNode<K, D>>>> = vec![];
+        while let Some((link, children_copied)) = stack.pop() {
+            match *link {
+                None => copies.push(None),
+                Some(ref node) if !children_copied => {
+                    stack.push((link, true));
+                    stack.push((&node.hi, false));
+                    stack.push((&node.eq, false));
+                    stack.push((&node.lo, false));
+                },
+                Some(ref node) => {
+                    let hi = copies.pop().expect("copied hi");
+                    let eq = copies.pop().expect("copied eq");
+                    let lo = copies.pop().expect("copied lo");
+                    copies.push(Some(Box::new(TstNode {
+                        key: node.key.clone(),
+                        lo,
+                        eq,
+                        hi,
+                        data: node.data.clone(),
+                    })));
+                },
+            }
+        }
+        TstTrie {
+            root: copies.pop().expect("the root is always copied last"),
+            empty: self.empty.clone(),
+        }
+    }
+}
+
+impl<K, D> Drop for TstTrie<K, D> {
+
+    /// Tear the tree down a node at a time, since the default recursive drop would overflow
+    /// the stack on a very long key
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<TstNode<K, D>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.lo.take());
+            stack.extend(node.eq.take());
+            stack.extend(node.hi.take());
+        }
+    }
+}
+
+impl<K: Copy + fmt::Debug, D: fmt::Debug> fmt::Debug for TstTrie<K, D> {
+
+    /// Print the entries as a map from whole keys to values, in key order
+    ///
+    /// Goes through [TstIter] rather than a derived (recursive) impl, which would overflow the
+    /// stack on a very long key.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(TstIter::new(self)).finish()
+    }
+}
+
+/// An iterator over the entries of a [TstTrie], in key order
+///
+/// Created by [TstTrie::iter]. Every node on the stack knows its depth, so the key being built
+/// is shared and only copied out for each value.
+pub struct TstIter<'a, K: 'a, D: 'a> {
+    /// the value under the empty key, which comes first
+    empty: Option<&'a D>,
+    /// nodes still to visit with their depth, and whether their `lo` side has been queued
+    stack: Vec<(&'a TstNode<K, D>, usize, bool)>,
+    key: Vec<K>,
+}
+
+impl<'a, K, D> TstIter<'a, K, D> {
+
+    fn new(tst: &'a TstTrie<K, D>) -> TstIter<'a, K, D> {
+        TstIter {
+            empty: tst.empty.as_ref(),
+            stack: tst.root.iter().map(|root| (&**root, 0, false)).collect(),
+            key: vec![],
+        }
+    }
+}
+
+impl<'a, K: Copy, D> Iterator for TstIter<'a, K, D> {
+    type Item = (Vec<K>, &'a D);
+
+    fn next(&mut self) -> Option<(Vec<K>, &'a D)> {
+        if let Some(data) = self.empty.take() {
+            return Some((vec![], data));
+        }
+        while let Some((node, depth, queued)) = self.stack.pop() {
+            if !queued {
+                // smaller elements first, then this node, its longer keys, and larger elements
+                self.stack.extend(node.hi.as_ref().map(|hi| (&**hi, depth, false)));
+                self.stack.extend(node.eq.as_ref().map(|eq| (&**eq, depth + 1, false)));
+                self.stack.push((node, depth, true));
+                self.stack.extend(node.lo.as_ref().map(|lo| (&**lo, depth, false)));
+                continue;
+            }
+            self.key.truncate(depth);
+            self.key.push(node.key);
+            if let Some(ref data) = node.data {
+                return Some((self.key.clone(), data));
+            }
+        }
+        None
+    }
+}
+
+impl<K, D> TstNode<K, D> {
+
+    fn new(key: K) -> TstNode<K, D> {
+        TstNode {
+            key,
+            lo: None,
+            eq: None,
+            hi: None,
+            data: None,
+        }
+    }
+}
+
+/// Store a value in an empty slot, or fail if there's one there already
+fn store<D>(slot: &mut Option<D>, data: D) -> ErrType {
+    match *slot {
+        None => {
+            *slot = Some(data);
+            Ok(())
+        },
+        Some(_) => Err(TrieError::KeyAlreadyPresent),
+    }
+}
+
+/// Walk the binary search tree of one key position for the node holding `key_elem`
+fn find<K: Ord, D>(mut link: &Option<Box<TstNode<K, D>>>, key_elem: K) -> Option<&TstNode<K, D>> {
+    while let Some(ref node) = *link {
+        link = match key_elem.cmp(&node.key) {
+            Ordering::Less => &node.lo,
+            Ordering::Greater => &node.hi,
+            Ordering::Equal => return Some(node),
+        };
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use {Trie, TrieError};
+    use super::TstTrie;
+
+    #[test]
+    fn test_tst_trie() {
+        let mut t = TstTrie::new_empty();
+        assert_eq!(t.insert("abc".chars(), 1), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 2), Ok(()));
+        assert_eq!(t.insert("ab".chars(), 3), Err(TrieError::KeyAlreadyPresent));
+
+        assert_eq!(t.get("abc".chars()), Some(&1));
+        assert_eq!(t.get("ab".chars()), Some(&2));
+        assert_eq!(t.get("a".chars()), None);
+        assert_eq!(t.get("".chars()), None);
+        assert_eq!(t.search("abcdef".chars()), Some(&1));
+        assert_eq!(t.search("abx".chars()), Some(&2));
+        assert_eq!(t.search("a".chars()), None);
+
+        assert_eq!(t.insert("".chars(), 0), Ok(()));
+        assert_eq!(t.insert("".chars(), 0), Err(TrieError::KeyAlreadyPresent));
+        assert_eq!(t.get("".chars()), Some(&0));
+        assert_eq!(t.search("x".chars()), Some(&0));
+    }
+
+    #[test]
+    fn test_iter_and_debug() {
+        let mut t = TstTrie::new_empty();
+        for (i, word) in ["b", "ab", "abc", "a", "ba"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let keys: Vec<String> = t.iter().map(|(k, _)| k.into_iter().collect()).collect();
+        assert_eq!(keys, vec!["a", "ab", "abc", "b", "ba"]);
+        assert_eq!(format!("{:?}", t),
+                   "{['a']: 3, ['a', 'b']: 1, ['a', 'b', 'c']: 2, ['b']: 0, ['b', 'a']: 4}");
+
+        assert_eq!(t.insert("".chars(), 5), Ok(()));
+        assert_eq!(t.iter().next(), Some((vec![], &5)));
+        assert_eq!(format!("{:?}", TstTrie::<char, usize>::new_empty()), "{}");
+    }
+
+    #[test]
+    fn test_tst_trie_matches_trie() {
+        let words = [
+            "", "a", "ab", "abc", "abd", "b", "ba", "bad", "cat", "car", "card", "care", "zebra",
+            "zeal", "m", "mm", "mmm",
+        ];
+        let mut t = Trie::new_empty();
+        let mut tst = TstTrie::new_empty();
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+            assert_eq!(tst.insert(word.chars(), i), Ok(()));
+        }
+        assert_eq!(tst.insert("card".chars(), 0), t.insert("card".chars(), 0));
+
+        let queries = [
+            "", "a", "abcd", "abx", "b", "bat", "c", "ca", "cards", "zeb", "zealous", "n", "mmmm",
+            "x",
+        ];
+        for query in words.iter().chain(queries.iter()) {
+            assert_eq!(tst.get(query.chars()), t.get(query.chars()), "get {:?}", query);
+            assert_eq!(tst.search(query.chars()), t.search(query.chars()), "search {:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_very_long_key() {
+        let len = 100_000;
+        let mut tst = TstTrie::new_empty();
+        assert_eq!(tst.insert((0..len).map(|i| i % 7), "long"), Ok(()));
+        assert_eq!(tst.insert((0..10).map(|i| i % 7), "short"), Ok(()));
+        assert_eq!(tst.get((0..len).map(|i| i % 7)), Some(&"long"));
+        assert_eq!(tst.search((0..len - 1).map(|i| i % 7)), Some(&"short"));
+
+        let copy = tst.clone();
+        drop(tst);
+        assert_eq!(copy.get((0..len).map(|i| i % 7)), Some(&"long"));
+        assert_eq!(copy.get((0..10).map(|i| i % 7)), Some(&"short"));
+        assert_eq!(copy.iter().count(), 2);
+        assert!(format!("{:?}", copy).ends_with(": \"long\"}"));
+    }
+}
@@ -0,0 +1,163 @@
+use Trie;
+
+/// Callbacks for a depth-first walk over a Trie, made with [Trie::walk]
+///
+/// Every method does nothing by default, so a visitor only implements the hooks it needs.
+/// Nothing is allocated on the visitor's behalf: keys aren't rebuilt, so a visitor which wants
+/// them keeps its own path from `enter` and `leave`.
+pub trait TrieVisitor<K, D> {
+
+    /// Called on reaching a node below the root, holding `key` at `depth` (1 for the root's
+    /// children)
+    fn enter(&mut self, _key: &K, _depth: usize) {}
+
+    /// Called on a node's value, after `enter` and before its children; depth 0 is the value
+    /// stored under the empty key
+    fn visit_value(&mut self, _depth: usize, _data: &D) {}
+
+    /// Called once the node entered with `key` at `depth`, and all of its children, are done
+    fn leave(&mut self, _key: &K, _depth: usize) {}
+}
+
+impl<K: PartialEq + Copy, D> Trie<K, D> {
+
+    /// Walk the whole Trie depth-first, in the order children were added, calling `visitor` as
+    /// each node is entered and left
+    pub fn walk<V: TrieVisitor<K, D>>(&self, visitor: &mut V) {
+        self.arrive(visitor, 0);
+        // each node being walked, with its depth and how many of its children have been walked
+        let mut stack = vec![(self, 0, 0)];
+        while let Some(&mut (node, depth, ref mut next)) = stack.last_mut() {
+            if let Some(child) = node.children.get(*next) {
+                *next += 1;
+                child.arrive(visitor, depth + 1);
+                stack.push((child, depth + 1, 0));
+                continue;
+            }
+            stack.pop();
+            if let Some(ref key) = node.key {
+                visitor.leave(key, depth);
+            }
+        }
+    }
+
+    /// Tell `visitor` about this node, before any of its children
+    fn arrive<V: TrieVisitor<K, D>>(&self, visitor: &mut V, depth: usize) {
+        if let Some(ref key) = self.key {
+            visitor.enter(key, depth);
+        }
+        if let Some(ref data) = self.data {
+            visitor.visit_value(depth, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use Trie;
+    use super::TrieVisitor;
+
+    /// Records every callback, in order
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl TrieVisitor<char, usize> for Recorder {
+        fn enter(&mut self, key: &char, depth: usize) {
+            self.events.push(format!("enter {} {}", key, depth));
+        }
+
+        fn visit_value(&mut self, depth: usize, data: &usize) {
+            self.events.push(format!("value {} {}", depth, data));
+        }
+
+        fn leave(&mut self, key: &char, depth: usize) {
+            self.events.push(format!("leave {} {}", key, depth));
+        }
+    }
+
+    #[test]
+    fn test_walk_order() {
+        let mut t = Trie::new_empty();
+        for (i, word) in ["b", "ab", "a", ""].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+
+        let mut recorder = Recorder::default();
+        t.walk(&mut recorder);
+        assert_eq!(recorder.events, vec![
+            "value 0 3",
+            "enter b 1", "value 1 0", "leave b 1",
+            "enter a 1", "value 1 2",
+            "enter b 2", "value 2 1", "leave b 2",
+            "leave a 1",
+        ]);
+    }
+
+    #[test]
+    fn test_walk_rebuilds_keys() {
+        /// Only needs two of the hooks to collect every key with its value
+        struct Keys {
+            path: Vec<char>,
+            found: Vec<(String, usize)>,
+        }
+
+        impl TrieVisitor<char, usize> for Keys {
+            fn enter(&mut self, key: &char, _: usize) {
+                self.path.push(*key);
+            }
+
+            fn visit_value(&mut self, _: usize, data: &usize) {
+                self.found.push((self.path.iter().collect(), *data));
+            }
+
+            fn leave(&mut self, _: &char, _: usize) {
+                self.path.pop();
+            }
+        }
+
+        let mut t = Trie::new_empty();
+        for (i, word) in ["car", "cat", "do"].iter().enumerate() {
+            assert_eq!(t.insert(word.chars(), i), Ok(()));
+        }
+        let mut keys = Keys { path: vec![], found: vec![] };
+        t.walk(&mut keys);
+        let expected: Vec<(String, usize)> = t.iter().map(|(k, &v)| (k.into_iter().collect(), v)).collect();
+        assert_eq!(keys.found, expected);
+
+        let mut nothing = Recorder::default();
+        Trie::new_empty().walk(&mut nothing);
+        assert!(nothing.events.is_empty());
+    }
+
+    #[test]
+    fn test_walk_very_long_key() {
+        /// Counts how deep the walk goes and how many nodes it leaves
+        #[derive(Default)]
+        struct Depth {
+            deepest: usize,
+            left: usize,
+        }
+
+        impl TrieVisitor<u8, &'static str> for Depth {
+            fn enter(&mut self, _: &u8, depth: usize) {
+                self.deepest = self.deepest.max(depth);
+            }
+
+            fn leave(&mut self, _: &u8, _: usize) {
+                self.left += 1;
+            }
+        }
+
+        let len = 100_000;
+        let mut t = Trie::new_empty();
+        assert_eq!(t.insert((0..len).map(|i| (i % 7) as u8), "long"), Ok(()));
+        let mut depth = Depth::default();
+        t.walk(&mut depth);
+        assert_eq!(depth.deepest, len);
+        assert_eq!(depth.left, len);
+    }
+}